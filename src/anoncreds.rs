@@ -1,11 +1,20 @@
 use {ErrorCode, IndyHandle};
 
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::time::Duration;
 use std::ptr::null;
 
+use serde_json;
+use serde_json::Value;
+
 use utils::callbacks::ClosureHandler;
 use utils::results::ResultHandler;
+use utils::fetch::Fetch;
+use utils::reply::ReplyHandler;
+
+use ledger::Ledger;
+use proof::{Proof, RevealedAttribute, RevealedAttributeGroup, VerifiedProof};
 
 use native::anoncreds;
 use native::{ResponseStringStringCB,
@@ -96,6 +105,120 @@ impl Issuer {
         })
     }
 
+    /// Generates new keys for an existing credential definition and keeps them as temporary
+    /// until `Issuer::rotate_credential_def_apply` is called.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `cred_def_id` - id of the credential definition stored in the wallet.
+    /// * `config_json` - credential definition config (see Issuer::create_and_store_credential_def).
+    ///
+    /// # Returns
+    /// * `cred_def_json` - the credential definition with the new keys, to be published on the ledger.
+    pub fn rotate_credential_def_start(wallet_handle: IndyHandle, cred_def_id: &str, config_json: Option<&str>) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Issuer::_rotate_credential_def_start(command_handle, wallet_handle, cred_def_id, config_json, cb);
+
+        ResultHandler::one(err, receiver)
+    }
+
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn rotate_credential_def_start_timeout(wallet_handle: IndyHandle, cred_def_id: &str, config_json: Option<&str>, timeout: Duration) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Issuer::_rotate_credential_def_start(command_handle, wallet_handle, cred_def_id, config_json, cb);
+
+        ResultHandler::one_timeout(err, receiver, timeout)
+    }
+
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn rotate_credential_def_start_async<F: 'static>(wallet_handle: IndyHandle, cred_def_id: &str, config_json: Option<&str>, closure: F) -> ErrorCode where F: FnMut(ErrorCode, String) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec_string(Box::new(closure));
+
+        Issuer::_rotate_credential_def_start(command_handle, wallet_handle, cred_def_id, config_json, cb)
+    }
+
+    fn _rotate_credential_def_start(command_handle: IndyHandle, wallet_handle: IndyHandle, cred_def_id: &str, config_json: Option<&str>, cb: Option<ResponseStringCB>) -> ErrorCode {
+        let cred_def_id = c_str!(cred_def_id);
+        let config_json_str = opt_c_str!(config_json);
+
+        ErrorCode::from(unsafe {
+          anoncreds::indy_issuer_rotate_credential_def_start(command_handle, wallet_handle, cred_def_id.as_ptr(), opt_c_ptr!(config_json, config_json_str), cb)
+        })
+    }
+
+    /// Replaces the keys of a credential definition with the temporary keys
+    /// generated by `Issuer::rotate_credential_def_start`.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `cred_def_id` - id of the credential definition stored in the wallet.
+    pub fn rotate_credential_def_apply(wallet_handle: IndyHandle, cred_def_id: &str) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Issuer::_rotate_credential_def_apply(command_handle, wallet_handle, cred_def_id, cb);
+
+        ResultHandler::empty(err, receiver)
+    }
+
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn rotate_credential_def_apply_timeout(wallet_handle: IndyHandle, cred_def_id: &str, timeout: Duration) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Issuer::_rotate_credential_def_apply(command_handle, wallet_handle, cred_def_id, cb);
+
+        ResultHandler::empty_timeout(err, receiver, timeout)
+    }
+
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn rotate_credential_def_apply_async<F: 'static>(wallet_handle: IndyHandle, cred_def_id: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec(Box::new(closure));
+
+        Issuer::_rotate_credential_def_apply(command_handle, wallet_handle, cred_def_id, cb)
+    }
+
+    fn _rotate_credential_def_apply(command_handle: IndyHandle, wallet_handle: IndyHandle, cred_def_id: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+        let cred_def_id = c_str!(cred_def_id);
+
+        ErrorCode::from(unsafe {
+          anoncreds::indy_issuer_rotate_credential_def_apply(command_handle, wallet_handle, cred_def_id.as_ptr(), cb)
+        })
+    }
+
+    /// Rotates the keys of a credential definition and publishes them on the ledger.
+    ///
+    /// Generates new keys (see `Issuer::rotate_credential_def_start`), sends the new CLAIM_DEF
+    /// signed by `issuer_did` and applies the new keys to the wallet only when the ledger accepted
+    /// the write. If the write fails the wallet keeps using the old keys.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `issuer_did` - DID of the issuer that owns the credential definition.
+    /// * `cred_def_id` - id of the credential definition stored in the wallet.
+    /// * `config_json` - credential definition config (see Issuer::create_and_store_credential_def).
+    ///
+    /// # Returns
+    /// * `cred_def_json` - the published credential definition.
+    pub fn rotate_credential_def(pool_handle: IndyHandle, wallet_handle: IndyHandle, issuer_did: &str, cred_def_id: &str, config_json: Option<&str>) -> Result<String, ErrorCode> {
+        let cred_def_json = Issuer::rotate_credential_def_start(wallet_handle, cred_def_id, config_json)?;
+
+        let request = Ledger::build_cred_def_request(issuer_did, &cred_def_json)?;
+        let response = Ledger::sign_and_submit_request(pool_handle, wallet_handle, issuer_did, &request)?;
+        ReplyHandler::check(&response)?;
+
+        Issuer::rotate_credential_def_apply(wallet_handle, cred_def_id)?;
+
+        Ok(cred_def_json)
+    }
+
     pub fn create_and_store_revoc_reg(wallet_handle: IndyHandle, issuer_did: &str, revoc_def_type: Option<&str>, tag: &str, cred_def_id: &str, config_json: &str, tails_writer_handle: IndyHandle) -> Result<(String, String, String), ErrorCode> {
         let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string_string_string();
 
@@ -753,6 +876,79 @@ impl Prover {
 pub struct Verifier {}
 
 impl Verifier {
+    /// Verifies a proof against the ledger artifacts it is based on.
+    ///
+    /// Fetches the schema, credential definition and (for revocable credentials) the revocation
+    /// registry definition and the registry at the timestamp of every identifier of the proof.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `proof_request_json` - proof request json (see Verifier::verify_proof).
+    /// * `proof_json` - proof json created by Prover::create_proof.
+    ///
+    /// # Returns
+    /// Whether the proof is valid and the revealed attributes and attribute groups by referent
+    /// with the ids of their schema and credential definition.
+    pub fn verify_proof_with_ledger(pool_handle: IndyHandle, proof_request_json: &str, proof_json: &str) -> Result<VerifiedProof, ErrorCode> {
+        let proof = Proof::from_json(proof_json)?;
+
+        let mut schemas = json!({});
+        let mut cred_defs = json!({});
+        let mut rev_reg_defs = json!({});
+        let mut rev_regs = json!({});
+
+        for identifier in &proof.identifiers {
+            if schemas.get(&identifier.schema_id).is_none() {
+                schemas[&identifier.schema_id] = Verifier::parse(&Fetch::schema(pool_handle, None, &identifier.schema_id)?)?;
+            }
+
+            if cred_defs.get(&identifier.cred_def_id).is_none() {
+                cred_defs[&identifier.cred_def_id] = Verifier::parse(&Fetch::cred_def(pool_handle, None, &identifier.cred_def_id)?)?;
+            }
+
+            if let (Some(rev_reg_id), Some(timestamp)) = (identifier.rev_reg_id.as_ref(), identifier.timestamp) {
+                if rev_reg_defs.get(rev_reg_id).is_none() {
+                    rev_reg_defs[rev_reg_id] = Verifier::parse(&Fetch::rev_reg_def(pool_handle, None, rev_reg_id)?)?;
+                    rev_regs[rev_reg_id] = json!({});
+                }
+
+                let (rev_reg_json, _) = Fetch::rev_reg(pool_handle, None, rev_reg_id, timestamp as i64)?;
+                rev_regs[rev_reg_id][timestamp.to_string()] = Verifier::parse(&rev_reg_json)?;
+            }
+        }
+
+        let valid = Verifier::verify_proof(proof_request_json, proof_json, &schemas.to_string(), &cred_defs.to_string(), &rev_reg_defs.to_string(), &rev_regs.to_string())?;
+
+        let mut revealed_attrs = HashMap::new();
+        for (referent, attr) in &proof.requested_proof.revealed_attrs {
+            let identifier = proof.identifier(attr.sub_proof_index)?;
+
+            revealed_attrs.insert(referent.clone(), RevealedAttribute {
+                raw: attr.raw.clone(),
+                encoded: attr.encoded.clone(),
+                schema_id: identifier.schema_id.clone(),
+                cred_def_id: identifier.cred_def_id.clone(),
+            });
+        }
+
+        let mut revealed_attr_groups = HashMap::new();
+        for (referent, group) in &proof.requested_proof.revealed_attr_groups {
+            let identifier = proof.identifier(group.sub_proof_index)?;
+
+            revealed_attr_groups.insert(referent.clone(), RevealedAttributeGroup {
+                values: group.values.clone(),
+                schema_id: identifier.schema_id.clone(),
+                cred_def_id: identifier.cred_def_id.clone(),
+            });
+        }
+
+        Ok(VerifiedProof { valid, revealed_attrs, revealed_attr_groups })
+    }
+
+    fn parse(json: &str) -> Result<Value, ErrorCode> {
+        serde_json::from_str(json).map_err(|_| ErrorCode::CommonInvalidState)
+    }
+
     pub fn verify_proof(proof_request_json: &str, proof_json: &str, schemas_json: &str, credential_defs_json: &str, rev_reg_defs_json: &str, rev_regs_json: &str) -> Result<bool, ErrorCode> {
         let (receiver, command_handle, cb) = ClosureHandler::cb_ec_bool();
 
@@ -871,4 +1067,48 @@ impl AnonCreds {
           anoncreds::indy_update_revocation_state(command_handle, blob_storage_reader_handle, rev_state_json.as_ptr(), rev_reg_def_json.as_ptr(), rev_reg_delta_json.as_ptr(), timestamp, cred_rev_id.as_ptr(), cb)
         })
     }
+
+    /// Gets the unqualified form (indy-sdk legacy form) of a fully qualified entity like a DID,
+    /// schema id, credential definition id, revocation registry id, or of a whole
+    /// schema, credential definition, credential offer or proof request json.
+    ///
+    /// # Arguments
+    /// * `entity` - target entity to disqualify.
+    ///
+    /// # Returns
+    /// * `res` - entity either in unqualified form or the original one if the casting isn't possible
+    pub fn to_unqualified(entity: &str) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = AnonCreds::_to_unqualified(command_handle, entity, cb);
+
+        ResultHandler::one(err, receiver)
+    }
+
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn to_unqualified_timeout(entity: &str, timeout: Duration) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = AnonCreds::_to_unqualified(command_handle, entity, cb);
+
+        ResultHandler::one_timeout(err, receiver, timeout)
+    }
+
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn to_unqualified_async<F: 'static>(entity: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode, String) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec_string(Box::new(closure));
+
+        AnonCreds::_to_unqualified(command_handle, entity, cb)
+    }
+
+    fn _to_unqualified(command_handle: IndyHandle, entity: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+        let entity = c_str!(entity);
+
+        ErrorCode::from(unsafe {
+          anoncreds::indy_to_unqualified(command_handle, entity.as_ptr(), cb)
+        })
+    }
 }
@@ -0,0 +1,204 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json;
+use serde_json::{Map, Value};
+
+use std::collections::HashMap;
+
+use anoncreds::Prover;
+use revocation_state::RevocationStateCache;
+use utils::fetch::Fetch;
+
+/// Credential as returned by Prover::get_credentials_for_proof_req.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialInfo {
+    pub referent: String,
+    pub attrs: HashMap<String, String>,
+    pub schema_id: String,
+    pub cred_def_id: String,
+    pub rev_reg_id: Option<String>,
+    pub cred_rev_id: Option<String>,
+}
+
+/// Interval the credential has to be non-revoked in, in seconds from Unix Epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct NonRevokedInterval {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+}
+
+/// Credential that satisfies an attribute or predicate of a proof request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialCandidate {
+    pub cred_info: CredentialInfo,
+    pub interval: Option<NonRevokedInterval>,
+}
+
+#[derive(Deserialize)]
+struct CredentialsForProofRequest {
+    #[serde(default)]
+    attrs: HashMap<String, Vec<CredentialCandidate>>,
+    #[serde(default)]
+    predicates: HashMap<String, Vec<CredentialCandidate>>,
+}
+
+/// Strategy `AutoProver` uses to pick one credential per attribute and predicate.
+pub trait CredentialSelector {
+    /// Picks a credential for the attribute or predicate `referent` of the proof request.
+    /// `None` fails the proof with `WalletItemNotFound`.
+    fn select<'a>(&self, referent: &str, candidates: &'a [CredentialCandidate]) -> Option<&'a CredentialCandidate>;
+}
+
+/// Picks the first credential the wallet returns.
+pub struct FirstCredential {}
+
+impl CredentialSelector for FirstCredential {
+    fn select<'a>(&self, _referent: &str, candidates: &'a [CredentialCandidate]) -> Option<&'a CredentialCandidate> {
+        candidates.first()
+    }
+}
+
+pub struct AutoProver {}
+
+impl AutoProver {
+    /// Creates a proof for a proof request from the credentials in the wallet.
+    ///
+    /// Selects a credential for every requested attribute and predicate, fetches their schemas and
+    /// credential definitions from the ledger and takes the revocation states for the requested
+    /// non-revoked intervals from `RevocationStateCache`. All attributes are revealed.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `proof_req_json` - proof request json (see Prover::create_proof).
+    /// * `master_secret_id` - the id of the master secret stored in the wallet.
+    /// * `tails_dir` - directory with the tails files of the revocation registries.
+    /// * `selector` - picks the credential for each attribute and predicate.
+    ///
+    /// # Returns
+    /// Proof json (see Prover::create_proof).
+    pub fn create_proof(pool_handle: IndyHandle, wallet_handle: IndyHandle, proof_req_json: &str, master_secret_id: &str, tails_dir: &str, selector: &CredentialSelector) -> Result<String, ErrorCode> {
+        let credentials = Prover::get_credentials_for_proof_req(wallet_handle, proof_req_json)?;
+        let credentials: CredentialsForProofRequest = serde_json::from_str(&credentials).map_err(|_| ErrorCode::CommonInvalidState)?;
+
+        let mut artifacts = Artifacts::new(pool_handle, wallet_handle, tails_dir);
+
+        let mut requested_attributes = Map::new();
+        for (referent, candidates) in &credentials.attrs {
+            let mut requested = artifacts.add(AutoProver::select(selector, referent, candidates)?)?;
+            requested["revealed"] = json!(true);
+            requested_attributes.insert(referent.clone(), requested);
+        }
+
+        let mut requested_predicates = Map::new();
+        for (referent, candidates) in &credentials.predicates {
+            let requested = artifacts.add(AutoProver::select(selector, referent, candidates)?)?;
+            requested_predicates.insert(referent.clone(), requested);
+        }
+
+        let requested_credentials = json!({
+            "self_attested_attributes": {},
+            "requested_attributes": requested_attributes,
+            "requested_predicates": requested_predicates,
+        });
+
+        Prover::create_proof(wallet_handle, proof_req_json, &requested_credentials.to_string(), master_secret_id,
+                             &artifacts.schemas.to_string(), &artifacts.cred_defs.to_string(), &artifacts.rev_states.to_string())
+    }
+
+    fn select<'a>(selector: &CredentialSelector, referent: &str, candidates: &'a [CredentialCandidate]) -> Result<&'a CredentialCandidate, ErrorCode> {
+        selector.select(referent, candidates).ok_or_else(|| {
+            warn!("No credential selected for {}", referent);
+            ErrorCode::WalletItemNotFound
+        })
+    }
+}
+
+/// Ledger artifacts of the selected credentials, fetched once per id.
+struct Artifacts<'a> {
+    pool_handle: IndyHandle,
+    wallet_handle: IndyHandle,
+    tails_dir: &'a str,
+    schemas: Value,
+    cred_defs: Value,
+    rev_states: Value,
+}
+
+impl<'a> Artifacts<'a> {
+    fn new(pool_handle: IndyHandle, wallet_handle: IndyHandle, tails_dir: &'a str) -> Artifacts<'a> {
+        Artifacts { pool_handle, wallet_handle, tails_dir, schemas: json!({}), cred_defs: json!({}), rev_states: json!({}) }
+    }
+
+    /// Fetches what the proof needs for the credential and returns the requested credential json.
+    fn add(&mut self, candidate: &CredentialCandidate) -> Result<Value, ErrorCode> {
+        let cred_info = &candidate.cred_info;
+
+        if self.schemas.get(&cred_info.schema_id).is_none() {
+            let schema = Fetch::schema(self.pool_handle, None, &cred_info.schema_id)?;
+            self.schemas[&cred_info.schema_id] = Artifacts::parse(&schema)?;
+        }
+
+        if self.cred_defs.get(&cred_info.cred_def_id).is_none() {
+            let cred_def = Fetch::cred_def(self.pool_handle, None, &cred_info.cred_def_id)?;
+            self.cred_defs[&cred_info.cred_def_id] = Artifacts::parse(&cred_def)?;
+        }
+
+        let mut requested = json!({"cred_id": cred_info.referent});
+
+        if let (Some(rev_reg_id), Some(interval)) = (cred_info.rev_reg_id.as_ref(), candidate.interval.as_ref()) {
+            let (rev_state, timestamp) = RevocationStateCache::get(self.pool_handle, self.wallet_handle, cred_info, interval, self.tails_dir)?;
+
+            if self.rev_states.get(rev_reg_id).is_none() {
+                self.rev_states[rev_reg_id] = json!({});
+            }
+            self.rev_states[rev_reg_id][timestamp.to_string()] = Artifacts::parse(&rev_state)?;
+
+            requested["timestamp"] = json!(timestamp);
+        }
+
+        Ok(requested)
+    }
+
+    fn parse(json: &str) -> Result<Value, ErrorCode> {
+        serde_json::from_str(json).map_err(|_| ErrorCode::CommonInvalidState)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn candidate(referent: &str) -> CredentialCandidate {
+        CredentialCandidate {
+            cred_info: CredentialInfo {
+                referent: referent.to_string(),
+                attrs: HashMap::new(),
+                schema_id: "schema".to_string(),
+                cred_def_id: "cred_def".to_string(),
+                rev_reg_id: None,
+                cred_rev_id: None,
+            },
+            interval: None,
+        }
+    }
+
+    #[test]
+    fn credentials_for_proof_request_parse() {
+        let credentials = r#"{"attrs": {"attr1_referent": [{"cred_info": {"referent": "cred1", "attrs": {"name": "Alex"}, "schema_id": "schema", "cred_def_id": "cred_def", "rev_reg_id": "rev_reg", "cred_rev_id": "1"}, "interval": {"from": null, "to": 100}}]}, "predicates": {}}"#;
+
+        let credentials: CredentialsForProofRequest = serde_json::from_str(credentials).unwrap();
+
+        let candidate = &credentials.attrs["attr1_referent"][0];
+        assert_eq!(candidate.cred_info.rev_reg_id, Some("rev_reg".to_string()));
+        assert_eq!(candidate.interval, Some(NonRevokedInterval { from: None, to: Some(100) }));
+        assert!(credentials.predicates.is_empty());
+    }
+
+    #[test]
+    fn first_credential_selects_first() {
+        let candidates = vec![candidate("cred1"), candidate("cred2")];
+
+        assert_eq!(FirstCredential {}.select("attr1_referent", &candidates), Some(&candidates[0]));
+        assert_eq!(FirstCredential {}.select("attr1_referent", &[]), None);
+    }
+}
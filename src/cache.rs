@@ -0,0 +1,351 @@
+use {ErrorCode, IndyHandle};
+
+use std::ffi::CString;
+use std::time::Duration;
+
+use native::cache;
+use native::{ResponseEmptyCB,
+          ResponseStringCB};
+
+use utils::callbacks::ClosureHandler;
+use utils::results::ResultHandler;
+
+pub struct Cache {}
+
+impl Cache {
+    /// Gets schema json data for the specified schema id.
+    /// If data is present inside of the cache, cached data is returned.
+    /// Otherwise data is fetched from the ledger and stored inside of the cache for future use.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `submitter_did` - DID of the submitter stored in secured Wallet.
+    /// * `id` - identifier of schema.
+    /// * `options_json` -
+    /// {
+    ///     noCache: (bool, optional, false by default) Skip usage of cache,
+    ///     noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+    ///     noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+    ///     minFresh: (int, optional, -1 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+    /// }
+    ///
+    /// # Returns
+    /// Schema json.
+    /// {
+    ///     id: identifier of schema
+    ///     attrNames: array of attribute name strings
+    ///     name: Schema's name string
+    ///     version: Schema's version string
+    ///     ver: Version of the Schema json
+    /// }
+    pub fn get_schema(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Cache::_get_schema(command_handle, pool_handle, wallet_handle, submitter_did, id, options_json, cb);
+
+        ResultHandler::one(err, receiver)
+    }
+
+    /// Gets schema json data for the specified schema id.
+    /// If data is present inside of the cache, cached data is returned.
+    /// Otherwise data is fetched from the ledger and stored inside of the cache for future use.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `submitter_did` - DID of the submitter stored in secured Wallet.
+    /// * `id` - identifier of schema.
+    /// * `options_json` -
+    /// {
+    ///     noCache: (bool, optional, false by default) Skip usage of cache,
+    ///     noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+    ///     noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+    ///     minFresh: (int, optional, -1 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+    /// }
+    /// * `timeout` - the maximum time this function waits for a response
+    ///
+    /// # Returns
+    /// Schema json.
+    /// {
+    ///     id: identifier of schema
+    ///     attrNames: array of attribute name strings
+    ///     name: Schema's name string
+    ///     version: Schema's version string
+    ///     ver: Version of the Schema json
+    /// }
+    pub fn get_schema_timeout(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str, timeout: Duration) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Cache::_get_schema(command_handle, pool_handle, wallet_handle, submitter_did, id, options_json, cb);
+
+        ResultHandler::one_timeout(err, receiver, timeout)
+    }
+
+    /// Gets schema json data for the specified schema id.
+    /// If data is present inside of the cache, cached data is returned.
+    /// Otherwise data is fetched from the ledger and stored inside of the cache for future use.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `submitter_did` - DID of the submitter stored in secured Wallet.
+    /// * `id` - identifier of schema.
+    /// * `options_json` -
+    /// {
+    ///     noCache: (bool, optional, false by default) Skip usage of cache,
+    ///     noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+    ///     noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+    ///     minFresh: (int, optional, -1 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+    /// }
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn get_schema_async<F: 'static>(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode, String) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec_string(Box::new(closure));
+
+        Cache::_get_schema(command_handle, pool_handle, wallet_handle, submitter_did, id, options_json, cb)
+    }
+
+    fn _get_schema(command_handle: IndyHandle, pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+        let submitter_did = c_str!(submitter_did);
+        let id = c_str!(id);
+        let options_json = c_str!(options_json);
+
+        ErrorCode::from(unsafe {
+            cache::indy_get_schema(command_handle, pool_handle, wallet_handle, submitter_did.as_ptr(), id.as_ptr(), options_json.as_ptr(), cb)
+        })
+    }
+
+    /// Gets credential definition json data for the specified credential definition id.
+    /// If data is present inside of the cache, cached data is returned.
+    /// Otherwise data is fetched from the ledger and stored inside of the cache for future use.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `submitter_did` - DID of the submitter stored in secured Wallet.
+    /// * `id` - identifier of credential definition.
+    /// * `options_json` -
+    /// {
+    ///     noCache: (bool, optional, false by default) Skip usage of cache,
+    ///     noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+    ///     noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+    ///     minFresh: (int, optional, -1 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+    /// }
+    ///
+    /// # Returns
+    /// Credential Definition json.
+    /// {
+    ///     id: string - identifier of credential definition
+    ///     schemaId: string - identifier of stored in ledger schema
+    ///     type: string - type of the credential definition. CL is the only supported type now.
+    ///     tag: string - allows to distinct between credential definitions for the same issuer and schema
+    ///     value: Dictionary with Credential Definition's data: {
+    ///         primary: primary credential public key,
+    ///         Optional<revocation>: revocation credential public key
+    ///     },
+    ///     ver: Version of the Credential Definition json
+    /// }
+    pub fn get_cred_def(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Cache::_get_cred_def(command_handle, pool_handle, wallet_handle, submitter_did, id, options_json, cb);
+
+        ResultHandler::one(err, receiver)
+    }
+
+    /// Gets credential definition json data for the specified credential definition id.
+    /// If data is present inside of the cache, cached data is returned.
+    /// Otherwise data is fetched from the ledger and stored inside of the cache for future use.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `submitter_did` - DID of the submitter stored in secured Wallet.
+    /// * `id` - identifier of credential definition.
+    /// * `options_json` -
+    /// {
+    ///     noCache: (bool, optional, false by default) Skip usage of cache,
+    ///     noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+    ///     noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+    ///     minFresh: (int, optional, -1 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+    /// }
+    /// * `timeout` - the maximum time this function waits for a response
+    ///
+    /// # Returns
+    /// Credential Definition json.
+    /// {
+    ///     id: string - identifier of credential definition
+    ///     schemaId: string - identifier of stored in ledger schema
+    ///     type: string - type of the credential definition. CL is the only supported type now.
+    ///     tag: string - allows to distinct between credential definitions for the same issuer and schema
+    ///     value: Dictionary with Credential Definition's data: {
+    ///         primary: primary credential public key,
+    ///         Optional<revocation>: revocation credential public key
+    ///     },
+    ///     ver: Version of the Credential Definition json
+    /// }
+    pub fn get_cred_def_timeout(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str, timeout: Duration) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Cache::_get_cred_def(command_handle, pool_handle, wallet_handle, submitter_did, id, options_json, cb);
+
+        ResultHandler::one_timeout(err, receiver, timeout)
+    }
+
+    /// Gets credential definition json data for the specified credential definition id.
+    /// If data is present inside of the cache, cached data is returned.
+    /// Otherwise data is fetched from the ledger and stored inside of the cache for future use.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `submitter_did` - DID of the submitter stored in secured Wallet.
+    /// * `id` - identifier of credential definition.
+    /// * `options_json` -
+    /// {
+    ///     noCache: (bool, optional, false by default) Skip usage of cache,
+    ///     noUpdate: (bool, optional, false by default) Use only cached data, do not try to update.
+    ///     noStore: (bool, optional, false by default) Skip storing fresh data if updated,
+    ///     minFresh: (int, optional, -1 by default) Return cached data if not older than this many seconds. -1 means do not check age.
+    /// }
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn get_cred_def_async<F: 'static>(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode, String) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec_string(Box::new(closure));
+
+        Cache::_get_cred_def(command_handle, pool_handle, wallet_handle, submitter_did, id, options_json, cb)
+    }
+
+    fn _get_cred_def(command_handle: IndyHandle, pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, id: &str, options_json: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+        let submitter_did = c_str!(submitter_did);
+        let id = c_str!(id);
+        let options_json = c_str!(options_json);
+
+        ErrorCode::from(unsafe {
+            cache::indy_get_cred_def(command_handle, pool_handle, wallet_handle, submitter_did.as_ptr(), id.as_ptr(), options_json.as_ptr(), cb)
+        })
+    }
+
+    /// Purges schema cache.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `options_json` -
+    /// {
+    ///     maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+    /// }
+    pub fn purge_schema_cache(wallet_handle: IndyHandle, options_json: &str) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Cache::_purge_schema_cache(command_handle, wallet_handle, options_json, cb);
+
+        ResultHandler::empty(err, receiver)
+    }
+
+    /// Purges schema cache.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `options_json` -
+    /// {
+    ///     maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+    /// }
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn purge_schema_cache_timeout(wallet_handle: IndyHandle, options_json: &str, timeout: Duration) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Cache::_purge_schema_cache(command_handle, wallet_handle, options_json, cb);
+
+        ResultHandler::empty_timeout(err, receiver, timeout)
+    }
+
+    /// Purges schema cache.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `options_json` -
+    /// {
+    ///     maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+    /// }
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn purge_schema_cache_async<F: 'static>(wallet_handle: IndyHandle, options_json: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec(Box::new(closure));
+
+        Cache::_purge_schema_cache(command_handle, wallet_handle, options_json, cb)
+    }
+
+    fn _purge_schema_cache(command_handle: IndyHandle, wallet_handle: IndyHandle, options_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+        let options_json = c_str!(options_json);
+
+        ErrorCode::from(unsafe {
+            cache::indy_purge_schema_cache(command_handle, wallet_handle, options_json.as_ptr(), cb)
+        })
+    }
+
+    /// Purges credential definition cache.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `options_json` -
+    /// {
+    ///     maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+    /// }
+    pub fn purge_cred_def_cache(wallet_handle: IndyHandle, options_json: &str) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Cache::_purge_cred_def_cache(command_handle, wallet_handle, options_json, cb);
+
+        ResultHandler::empty(err, receiver)
+    }
+
+    /// Purges credential definition cache.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `options_json` -
+    /// {
+    ///     maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+    /// }
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn purge_cred_def_cache_timeout(wallet_handle: IndyHandle, options_json: &str, timeout: Duration) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Cache::_purge_cred_def_cache(command_handle, wallet_handle, options_json, cb);
+
+        ResultHandler::empty_timeout(err, receiver, timeout)
+    }
+
+    /// Purges credential definition cache.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) that stores the cache.
+    /// * `options_json` -
+    /// {
+    ///     maxAge: (int, optional, -1 by default) Purge cached data if older than this many seconds. -1 means purge all.
+    /// }
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn purge_cred_def_cache_async<F: 'static>(wallet_handle: IndyHandle, options_json: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec(Box::new(closure));
+
+        Cache::_purge_cred_def_cache(command_handle, wallet_handle, options_json, cb)
+    }
+
+    fn _purge_cred_def_cache(command_handle: IndyHandle, wallet_handle: IndyHandle, options_json: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+        let options_json = c_str!(options_json);
+
+        ErrorCode::from(unsafe {
+            cache::indy_purge_cred_def_cache(command_handle, wallet_handle, options_json.as_ptr(), cb)
+        })
+    }
+}
@@ -0,0 +1,300 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json;
+
+use std::collections::HashMap;
+
+use crypto::Crypto;
+use did::Did;
+use pairwise::Pairwise;
+use utils::base58::Base58;
+use utils::records::{Record, Records};
+
+const INVITATION_RECORD: &'static str = "IndyWrapper::ConnectionInvitation";
+
+/// Invitation to connect, sent in plain to the invitee.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Invitation {
+    /// DID the inviter created for the connection.
+    pub did: String,
+    pub verkey: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// The invitee sent the connection request.
+    Requested,
+    /// The inviter sent the connection response.
+    Responded,
+    /// The invitee received the connection response.
+    Complete,
+}
+
+/// Metadata of a connection, stored as the metadata of the pairwise.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionMetadata {
+    /// Label of the other party.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Endpoint of the other party.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    pub state: ConnectionState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairwiseConnection {
+    pub my_did: String,
+    pub their_did: String,
+    pub metadata: ConnectionMetadata,
+}
+
+/// Connection request, auth-crypted by the invitee to the verkey of the invitation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ConnectionRequest {
+    did: String,
+    verkey: String,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    endpoint: Option<String>,
+}
+
+/// Connection response, auth-crypted by the inviter to the verkey of the request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ConnectionResponse {
+    did: String,
+}
+
+#[derive(Deserialize)]
+struct PairwiseInfo {
+    my_did: String,
+    metadata: Option<String>,
+}
+
+/// Establishes pairwise connections.
+///
+/// 1. The inviter creates an `Invitation` with `Connection::create_invitation`.
+/// 2. The invitee answers it with the request of `Connection::request`.
+/// 3. The inviter answers the request with the response of `Connection::respond`.
+/// 4. The invitee accepts the response with `Connection::complete`.
+///
+/// Both parties use a new DID per connection. Requests and responses are auth-crypted and the
+/// sender verkey is checked against the DID the other party announced.
+pub struct Connection {}
+
+impl Connection {
+    /// Creates a DID for a new connection and an invitation to it.
+    ///
+    /// The invitation is kept in a non-secret wallet record until a request for it is answered.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `label` - label of the inviter shown to the invitee (optional).
+    /// * `endpoint` - endpoint of the inviter (optional).
+    pub fn create_invitation(wallet_handle: IndyHandle, label: Option<&str>, endpoint: Option<&str>) -> Result<Invitation, ErrorCode> {
+        let (did, verkey) = Did::new(wallet_handle, "{}")?;
+
+        let invitation = Invitation { did, verkey, label: label.map(String::from), endpoint: endpoint.map(String::from) };
+
+        Records::add(wallet_handle, INVITATION_RECORD, &invitation.did, &invitation, &HashMap::new())?;
+
+        Ok(invitation)
+    }
+
+    /// Accepts an invitation: creates a DID for the connection, stores the pairwise in state
+    /// `Requested` and returns the connection request for the inviter.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `invitation` - the invitation received from the inviter.
+    /// * `label` - label of the invitee shown to the inviter (optional).
+    /// * `endpoint` - endpoint of the invitee (optional).
+    ///
+    /// # Returns
+    /// The encrypted connection request. `CommonInvalidStructure` if the DID of the invitation is
+    /// not derived from its verkey and `WalletItemAlreadyExists` if it is known to the wallet.
+    pub fn request(wallet_handle: IndyHandle, invitation: &Invitation, label: Option<&str>, endpoint: Option<&str>) -> Result<Vec<u8>, ErrorCode> {
+        Connection::check_their_did(wallet_handle, &invitation.did, &invitation.verkey)?;
+
+        let (did, verkey) = Did::new(wallet_handle, "{}")?;
+
+        let request = ConnectionRequest { did: did.clone(), verkey: verkey.clone(), label: label.map(String::from), endpoint: endpoint.map(String::from) };
+        let request = serde_json::to_vec(&request).map_err(|_| ErrorCode::CommonInvalidState)?;
+
+        let encrypted = Crypto::auth_crypt(wallet_handle, &verkey, &invitation.verkey, &request)?;
+
+        let metadata = ConnectionMetadata { label: invitation.label.clone(), endpoint: invitation.endpoint.clone(), state: ConnectionState::Requested };
+        Connection::store(wallet_handle, &did, &invitation.did, &invitation.verkey, &metadata)?;
+
+        Ok(encrypted)
+    }
+
+    /// Answers a connection request: stores the pairwise in state `Responded`, closes the
+    /// invitation and returns the connection response for the invitee.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `invitation_did` - DID of the invitation (`Invitation::did`) the request answers.
+    /// * `request` - the encrypted connection request.
+    ///
+    /// # Returns
+    /// * `connection` - the new connection.
+    /// * `response` - the encrypted connection response.
+    /// `WalletItemNotFound` if the invitation is not open, `CommonInvalidStructure` if the
+    /// request was not sent with the verkey it announces or its DID is not derived from the
+    /// verkey and `WalletItemAlreadyExists` if the DID of the request is known to the wallet.
+    pub fn respond(wallet_handle: IndyHandle, invitation_did: &str, request: &[u8]) -> Result<(PairwiseConnection, Vec<u8>), ErrorCode> {
+        let invitation: Record<Invitation> = Records::get(wallet_handle, INVITATION_RECORD, invitation_did)?
+            .ok_or(ErrorCode::WalletItemNotFound)?;
+        let invitation = invitation.value;
+
+        let (sender_verkey, request) = Crypto::auth_decrypt(wallet_handle, &invitation.verkey, request)?;
+        let request: ConnectionRequest = serde_json::from_slice(&request).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        if sender_verkey != request.verkey {
+            warn!("Connection request of {} was not sent with its verkey", request.did);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        Connection::check_their_did(wallet_handle, &request.did, &request.verkey)?;
+
+        let response = serde_json::to_vec(&ConnectionResponse { did: invitation.did.clone() }).map_err(|_| ErrorCode::CommonInvalidState)?;
+        let encrypted = Crypto::auth_crypt(wallet_handle, &invitation.verkey, &request.verkey, &response)?;
+
+        let metadata = ConnectionMetadata { label: request.label, endpoint: request.endpoint, state: ConnectionState::Responded };
+        Connection::store(wallet_handle, &invitation.did, &request.did, &request.verkey, &metadata)?;
+
+        Records::delete(wallet_handle, INVITATION_RECORD, invitation_did)?;
+
+        let connection = PairwiseConnection { my_did: invitation.did, their_did: request.did, metadata };
+
+        Ok((connection, encrypted))
+    }
+
+    /// Accepts the connection response and sets the connection to state `Complete`.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `their_did` - DID of the inviter (`Invitation::did`).
+    /// * `response` - the encrypted connection response.
+    ///
+    /// # Returns
+    /// The completed connection. `CommonInvalidStructure` if the response was not sent by the
+    /// inviter and `CommonInvalidState` if the connection is not in state `Requested`.
+    pub fn complete(wallet_handle: IndyHandle, their_did: &str, response: &[u8]) -> Result<PairwiseConnection, ErrorCode> {
+        let mut connection = Connection::get(wallet_handle, their_did)?;
+
+        if connection.metadata.state != ConnectionState::Requested {
+            return Err(ErrorCode::CommonInvalidState);
+        }
+
+        let my_verkey = Did::get_ver_key_local(wallet_handle, &connection.my_did)?;
+        let their_verkey = Did::get_ver_key_local(wallet_handle, their_did)?;
+
+        let (sender_verkey, response) = Crypto::auth_decrypt(wallet_handle, &my_verkey, response)?;
+        let response: ConnectionResponse = serde_json::from_slice(&response).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        if sender_verkey != their_verkey || response.did != their_did {
+            warn!("Connection response was not sent by {}", their_did);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        connection.metadata.state = ConnectionState::Complete;
+        Connection::set_metadata(wallet_handle, their_did, &connection.metadata)?;
+
+        Ok(connection)
+    }
+
+    /// Returns a connection by the DID of the other party.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the pairwise was not created by `Connection`.
+    pub fn get(wallet_handle: IndyHandle, their_did: &str) -> Result<PairwiseConnection, ErrorCode> {
+        let pairwise: PairwiseInfo = serde_json::from_str(&Pairwise::get(wallet_handle, their_did)?).map_err(|_| ErrorCode::CommonInvalidState)?;
+
+        let metadata = pairwise.metadata.ok_or(ErrorCode::CommonInvalidStructure)?;
+        let metadata: ConnectionMetadata = serde_json::from_str(&metadata).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        Ok(PairwiseConnection { my_did: pairwise.my_did, their_did: their_did.to_string(), metadata })
+    }
+
+    /// Their DID must be new to the wallet and derived from their verkey (the first 16 bytes of
+    /// the verkey), so that a connection can not take over a DID of another connection.
+    fn check_their_did(wallet_handle: IndyHandle, their_did: &str, their_verkey: &str) -> Result<(), ErrorCode> {
+        if !Connection::is_derived(their_did, their_verkey) {
+            warn!("DID {} is not derived from verkey {}", their_did, their_verkey);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        if Pairwise::does_exist(wallet_handle, their_did)? {
+            warn!("Pairwise for {} already exists", their_did);
+            return Err(ErrorCode::WalletItemAlreadyExists);
+        }
+
+        match Did::get_ver_key_local(wallet_handle, their_did) {
+            Ok(_) => {
+                warn!("DID {} already exists", their_did);
+                Err(ErrorCode::WalletItemAlreadyExists)
+            }
+            Err(ErrorCode::WalletItemNotFound) => Ok(()),
+            Err(err) => Err(err)
+        }
+    }
+
+    fn is_derived(did: &str, verkey: &str) -> bool {
+        match Base58::decode(verkey) {
+            Some(ref verkey) if verkey.len() == 32 => Base58::encode(&verkey[..16]) == did,
+            _ => false
+        }
+    }
+
+    fn store(wallet_handle: IndyHandle, my_did: &str, their_did: &str, their_verkey: &str, metadata: &ConnectionMetadata) -> Result<(), ErrorCode> {
+        Did::store_their_did(wallet_handle, &json!({"did": their_did, "verkey": their_verkey}).to_string())?;
+
+        let metadata = serde_json::to_string(metadata).map_err(|_| ErrorCode::CommonInvalidState)?;
+        Pairwise::create(wallet_handle, their_did, my_did, Some(&metadata))
+    }
+
+    fn set_metadata(wallet_handle: IndyHandle, their_did: &str, metadata: &ConnectionMetadata) -> Result<(), ErrorCode> {
+        let metadata = serde_json::to_string(metadata).map_err(|_| ErrorCode::CommonInvalidState)?;
+        Pairwise::set_metadata(wallet_handle, their_did, Some(&metadata))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn connection_metadata_serialize() {
+        let metadata = ConnectionMetadata { label: Some("Alice".to_string()), endpoint: None, state: ConnectionState::Responded };
+
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json, json!({"label": "Alice", "endpoint": null, "state": "responded"}));
+
+        assert_eq!(serde_json::from_value::<ConnectionMetadata>(json).unwrap(), metadata);
+    }
+
+    #[test]
+    fn is_derived_works() {
+        assert!(Connection::is_derived("VsKV7grR1BUE29mG2Fm2kX", "GjZWsBLgZCR18aL468JAT7w9CZRiBnpxUPPgyQxh4voa"));
+
+        assert!(!Connection::is_derived("CnEDk9HrMnmiHXEV1WFgbVCRteYnPqsJwrTdcZaNhFVW", "GjZWsBLgZCR18aL468JAT7w9CZRiBnpxUPPgyQxh4voa"));
+        assert!(!Connection::is_derived("VsKV7grR1BUE29mG2Fm2kX", "~HYwqs2vrTc8Tn4uBV7NBTe"));
+        assert!(!Connection::is_derived("VsKV7grR1BUE29mG2Fm2kX", "not base58 0OIl"));
+    }
+
+    #[test]
+    fn invitation_parse() {
+        let invitation: Invitation = serde_json::from_str(r#"{"did": "VsKV7grR1BUE29mG2Fm2kX", "verkey": "GjZWsBLgZCR18aL468JAT7w9CZRiBnpxUPPgyQxh4voa"}"#).unwrap();
+
+        assert_eq!(invitation.label, None);
+        assert_eq!(invitation.endpoint, None);
+    }
+}
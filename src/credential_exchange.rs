@@ -0,0 +1,107 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json;
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+use anoncreds::Prover;
+use utils::fetch::Fetch;
+use utils::records::{Record, Records};
+
+const EXCHANGE_RECORD: &'static str = "IndyWrapper::CredentialExchange";
+
+/// State kept in the wallet between `CredentialExchange::request_credential` and
+/// `CredentialExchange::store_credential`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct PendingExchange {
+    cred_def_id: String,
+    cred_def_json: String,
+    cred_req_metadata_json: String,
+}
+
+/// Holder side of credential issuance.
+///
+/// The credential request metadata is kept in a non-secret wallet record named by the
+/// exchange id until the credential is stored, so both steps can run in different processes.
+pub struct CredentialExchange {}
+
+impl CredentialExchange {
+    /// Creates a credential request for an offer.
+    ///
+    /// Fetches the credential definition of the offer from the ledger and keeps it with the
+    /// request metadata in the wallet.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `exchange_id` - id of the exchange, unique in the wallet, passed to `CredentialExchange::store_credential`.
+    /// * `prover_did` - a DID of the prover.
+    /// * `cred_offer_json` - credential offer as a json containing information about the issuer and a credential.
+    /// * `master_secret_id` - the id of the master secret stored in the wallet.
+    ///
+    /// # Returns
+    /// The credential request json to send to the issuer.
+    /// `WalletItemAlreadyExists` if an exchange with the id is pending.
+    pub fn request_credential(pool_handle: IndyHandle, wallet_handle: IndyHandle, exchange_id: &str, prover_did: &str, cred_offer_json: &str, master_secret_id: &str) -> Result<String, ErrorCode> {
+        let cred_offer: Value = serde_json::from_str(cred_offer_json).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+        let cred_def_id = cred_offer["cred_def_id"].as_str().ok_or(ErrorCode::CommonInvalidStructure)?;
+
+        let cred_def_json = Fetch::cred_def(pool_handle, Some(prover_did), cred_def_id)?;
+
+        let (cred_req_json, cred_req_metadata_json) = Prover::create_credential_req(wallet_handle, prover_did, cred_offer_json, &cred_def_json, master_secret_id)?;
+
+        let exchange = PendingExchange { cred_def_id: cred_def_id.to_string(), cred_def_json, cred_req_metadata_json };
+
+        let mut tags = HashMap::new();
+        tags.insert("cred_def_id".to_string(), exchange.cred_def_id.clone());
+        Records::add(wallet_handle, EXCHANGE_RECORD, exchange_id, &exchange, &tags)?;
+
+        Ok(cred_req_json)
+    }
+
+    /// Stores the credential issued for a request of `CredentialExchange::request_credential`.
+    ///
+    /// Fetches the revocation registry definition from the ledger if the credential is revocable.
+    /// The exchange is removed from the wallet once the credential is stored.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `exchange_id` - id of the exchange passed to `CredentialExchange::request_credential`.
+    /// * `cred_json` - credential json received from the issuer.
+    /// * `cred_id` - identifier by which credential will be stored in the wallet (optional).
+    ///
+    /// # Returns
+    /// The id of the stored credential.
+    /// `WalletItemNotFound` if there is no pending exchange with the id.
+    pub fn store_credential(pool_handle: IndyHandle, wallet_handle: IndyHandle, exchange_id: &str, cred_json: &str, cred_id: Option<&str>) -> Result<String, ErrorCode> {
+        let exchange: Record<PendingExchange> = Records::get(wallet_handle, EXCHANGE_RECORD, exchange_id)?
+            .ok_or(ErrorCode::WalletItemNotFound)?;
+        let exchange = exchange.value;
+
+        let credential: Value = serde_json::from_str(cred_json).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        if credential["cred_def_id"].as_str() != Some(&exchange.cred_def_id) {
+            warn!("Credential does not match the credential definition of exchange {}", exchange_id);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        let rev_reg_def_json = match credential["rev_reg_id"].as_str() {
+            Some(rev_reg_id) => Some(Fetch::rev_reg_def(pool_handle, None, rev_reg_id)?),
+            None => None
+        };
+
+        let cred_id = Prover::store_credential(wallet_handle, cred_id, &exchange.cred_req_metadata_json, cred_json, &exchange.cred_def_json,
+                                               rev_reg_def_json.as_ref().map(String::as_str))?;
+
+        Records::delete(wallet_handle, EXCHANGE_RECORD, exchange_id)?;
+
+        Ok(cred_id)
+    }
+
+    /// Drops a pending exchange, e.g. when the issuer declined the request.
+    pub fn cancel(wallet_handle: IndyHandle, exchange_id: &str) -> Result<(), ErrorCode> {
+        Records::delete(wallet_handle, EXCHANGE_RECORD, exchange_id)
+    }
+}
@@ -0,0 +1,150 @@
+use ErrorCode;
+
+use num_bigint::BigUint;
+use serde_json;
+use sha2::{Digest, Sha256};
+
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct AttributeValue {
+    raw: String,
+    encoded: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaAttrs {
+    attr_names: Vec<String>,
+}
+
+/// Builds `cred_values_json` for Issuer::create_credential.
+///
+/// Only the attributes of the schema are accepted and all of them have to be set.
+/// Names are compared like libindy does, ignoring case and spaces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialValues {
+    /// Schema attribute names by their normalized form.
+    attr_names: HashMap<String, String>,
+    values: BTreeMap<String, AttributeValue>,
+}
+
+impl CredentialValues {
+    /// Creates a builder for credentials of a schema.
+    ///
+    /// # Arguments
+    /// * `schema_json` - schema json, e.g. as returned by Ledger::parse_get_schema_response.
+    pub fn new(schema_json: &str) -> Result<CredentialValues, ErrorCode> {
+        let schema: SchemaAttrs = serde_json::from_str(schema_json).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        Ok(CredentialValues {
+            attr_names: schema.attr_names.into_iter().map(|name| (CredentialValues::normalize(&name), name)).collect(),
+            values: BTreeMap::new(),
+        })
+    }
+
+    /// Sets an attribute to `raw` encoded with `CredentialValues::encode`.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the schema has no such attribute.
+    pub fn add(&mut self, name: &str, raw: &str) -> Result<&mut CredentialValues, ErrorCode> {
+        self.add_encoded(name, raw, &CredentialValues::encode(raw))
+    }
+
+    /// Sets an attribute to `raw` with an own encoding.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the schema has no such attribute.
+    pub fn add_encoded(&mut self, name: &str, raw: &str, encoded: &str) -> Result<&mut CredentialValues, ErrorCode> {
+        let name = match self.attr_names.get(&CredentialValues::normalize(name)) {
+            Some(name) => name.clone(),
+            None => {
+                warn!("Attribute {} is not in the schema", name);
+                return Err(ErrorCode::CommonInvalidStructure);
+            }
+        };
+
+        self.values.insert(name, AttributeValue { raw: raw.to_string(), encoded: encoded.to_string() });
+
+        Ok(self)
+    }
+
+    /// Returns `cred_values_json`.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if an attribute of the schema is not set.
+    pub fn to_json(&self) -> Result<String, ErrorCode> {
+        let missing: Vec<&String> = self.attr_names.values().filter(|name| !self.values.contains_key(*name)).collect();
+
+        if !missing.is_empty() {
+            warn!("Attributes {:?} are not set", missing);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        serde_json::to_string(&self.values).map_err(|_| ErrorCode::CommonInvalidState)
+    }
+
+    /// Encodes a raw value the standard Indy way: 32-bit integers in canonical form (no sign or
+    /// leading zeros, e.g. not "+7" or "007") are kept, anything else becomes the decimal
+    /// representation of the SHA-256 hash of its UTF-8 bytes.
+    pub fn encode(raw: &str) -> String {
+        match raw.parse::<i32>() {
+            Ok(value) if value.to_string() == raw => raw.to_string(),
+            _ => BigUint::from_bytes_be(&Sha256::digest(raw.as_bytes())).to_str_radix(10)
+        }
+    }
+
+    fn normalize(name: &str) -> String {
+        name.replace(" ", "").to_lowercase()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SCHEMA: &str = r#"{"ver": "1.0", "id": "schema", "name": "gvt", "version": "1.0", "attrNames": ["name", "age"], "seqNo": 10}"#;
+
+    #[test]
+    fn encode_works() {
+        assert_eq!(CredentialValues::encode("28"), "28");
+        assert_eq!(CredentialValues::encode("-28"), "-28");
+        assert_eq!(CredentialValues::encode("Alex"), "99262857098057710338306967609588410025648622308394250666849665532448612202874");
+        assert_eq!(CredentialValues::encode("2147483648"), "26221484005389514539852548961319751347124425277437769688639924217837557266135");
+        assert_eq!(CredentialValues::encode(""), "102987336249554097029535212322581322789799900648198034993379397001115665086549");
+    }
+
+    #[test]
+    fn encode_hashes_non_canonical_integers() {
+        assert_eq!(CredentialValues::encode("007"), "44608119095630492481017134257834365612796282458274476478873559534504197876631");
+        assert_eq!(CredentialValues::encode("+7"), "51892283313854278470432636132668760852930530231058922456666353471300754924873");
+    }
+
+    #[test]
+    fn to_json_works() {
+        let mut values = CredentialValues::new(SCHEMA).unwrap();
+        values.add("Name", "abc").unwrap().add("age", "28").unwrap();
+
+        let values: serde_json::Value = serde_json::from_str(&values.to_json().unwrap()).unwrap();
+
+        assert_eq!(values, json!({
+            "name": {"raw": "abc", "encoded": "84342368487090800366523834928142263660104883695016514377462985829716817089965"},
+            "age": {"raw": "28", "encoded": "28"}
+        }));
+    }
+
+    #[test]
+    fn add_refuses_extra_attribute() {
+        let mut values = CredentialValues::new(SCHEMA).unwrap();
+
+        assert_eq!(values.add("height", "175").unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn to_json_refuses_missing_attribute() {
+        let mut values = CredentialValues::new(SCHEMA).unwrap();
+        values.add_encoded("name", "Alex", "1").unwrap();
+
+        assert_eq!(values.to_json().unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
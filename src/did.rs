@@ -3,17 +3,27 @@ use {ErrorCode, IndyHandle};
 use std::ffi::CString;
 use std::time::Duration;
 
+use issuer_setup::ReadRetries;
+use ledger::Ledger;
 use native::did;
 use native::{ResponseEmptyCB,
           ResponseStringCB,
           ResponseStringStringCB};
 
 use utils::callbacks::ClosureHandler;
+use storage::Tags;
+use utils::fetch::Fetch;
+use utils::records::{Record, Records};
+use utils::reply::ReplyHandler;
 use utils::results::ResultHandler;
 
+
 pub struct Did {}
 
 impl Did {
+    /// Type of the non-secret wallet records `Did::rotate_key` keeps the pending verkey of a DID in.
+    pub const PENDING_VERKEY_RECORD: &'static str = "IndyWrapper::PendingVerkey";
+
     /// Creates keys (signing and encryption keys) for a new
     /// DID (owned by the caller of the library).
     /// Identity's DID must be either explicitly provided, or taken as the first 16 bit of verkey.
@@ -35,6 +45,8 @@ impl Did {
     ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
     ///               currently only 'ed25519' value is supported for this field)
     ///     "cid": bool, (optional; if not set then false is used;)
+    ///     "method_name": string, (optional; method name to create fully qualified did, e.g. "sov";
+    ///               if not set then an unqualified did is created)
     /// }
     ///
     /// # Returns
@@ -70,6 +82,8 @@ impl Did {
     ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
     ///               currently only 'ed25519' value is supported for this field)
     ///     "cid": bool, (optional; if not set then false is used;)
+    ///     "method_name": string, (optional; method name to create fully qualified did, e.g. "sov";
+    ///               if not set then an unqualified did is created)
     /// }
     ///
     /// # Returns
@@ -105,6 +119,8 @@ impl Did {
     ///     "crypto_type": string, (optional; if not set then ed25519 curve is used;
     ///               currently only 'ed25519' value is supported for this field)
     ///     "cid": bool, (optional; if not set then false is used;)
+    ///     "method_name": string, (optional; method name to create fully qualified did, e.g. "sov";
+    ///               if not set then an unqualified did is created)
     /// }
     ///
     /// # Returns
@@ -253,6 +269,128 @@ impl Did {
         ErrorCode::from(unsafe { did::indy_replace_keys_apply(command_handle, wallet_handle, tgt_did.as_ptr(), cb) })
     }
 
+    /// Rotates the keys of a DID (owned by the caller of the library) on the ledger and in the wallet.
+    ///
+    /// Generates temporary keys (Did::replace_keys_start), writes a NYM with the new verkey signed
+    /// with the current key, reads the NYM back until the ledger returns the new verkey and only
+    /// then applies the temporary keys (Did::replace_keys_apply).
+    ///
+    /// If the write fails, the keys are not applied and the wallet keeps signing with the current
+    /// key. The temporary keys are replaced by the next rotation.
+    ///
+    /// The NYM is read back with `ReadRetries::default()`. If the NYM is written but the new verkey
+    /// does not show up, `PoolLedgerTimeout` is returned and the temporary keys stay pending; their
+    /// verkey is kept in a `Did::PENDING_VERKEY_RECORD` record until they are applied. Calling
+    /// `Did::rotate_key` again resumes the rotation: when the ledger already returns another verkey
+    /// than the wallet, the pending keys are applied if it is theirs, instead of starting a new rotation.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handler (created by Wallet::open).
+    /// * `did` - DID to rotate the keys of, it signs the NYM.
+    /// * `identity_json` - Identity information as json (see Did::replace_keys_start).
+    ///
+    /// # Returns
+    /// * `verkey` - The new verification key of the DID.
+    /// `LedgerInvalidTransaction` if the ledger rejects the NYM, `PoolLedgerTimeout` if the NYM
+    /// is written but the ledger does not return the new verkey yet and `WalletItemNotFound` if
+    /// the verkey on the ledger is not one of the keys of the wallet.
+    pub fn rotate_key(pool_handle: IndyHandle, wallet_handle: IndyHandle, did: &str, identity_json: &str) -> Result<String, ErrorCode> {
+        Did::rotate_key_with_retries(pool_handle, wallet_handle, did, identity_json, ReadRetries::default())
+    }
+
+    /// Rotates the keys of a DID (owned by the caller of the library) on the ledger and in the wallet.
+    ///
+    /// Same as `Did::rotate_key`, with the given number of GET_NYM reads and delay between them.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handler (created by Wallet::open).
+    /// * `did` - DID to rotate the keys of, it signs the NYM.
+    /// * `identity_json` - Identity information as json (see Did::replace_keys_start).
+    /// * `retries` - how the written NYM is read back.
+    ///
+    /// # Returns
+    /// * `verkey` - The new verification key of the DID.
+    pub fn rotate_key_with_retries(pool_handle: IndyHandle, wallet_handle: IndyHandle, did: &str, identity_json: &str, retries: ReadRetries) -> Result<String, ErrorCode> {
+        let current = Did::get_ver_key_local(wallet_handle, did)?;
+
+        if let Some(ledger_verkey) = Fetch::nym(pool_handle, Some(did), did)?.and_then(|nym| nym.verkey) {
+            if !Did::_is_verkey(did, &ledger_verkey, &current)? {
+                info!("Resuming the key rotation of {}", did);
+                return Did::_apply_pending_keys(wallet_handle, did, &ledger_verkey);
+            }
+        }
+
+        let verkey = Did::replace_keys_start(wallet_handle, did, identity_json)?;
+        Did::_set_pending_verkey(wallet_handle, did, &verkey)?;
+
+        let request = Ledger::build_nym_request(did, did, Some(&verkey), None, None)?;
+        let response = Ledger::sign_and_submit_request(pool_handle, wallet_handle, did, &request)?;
+        ReplyHandler::check(&response)?;
+
+        Did::_confirm_verkey(pool_handle, did, &verkey, retries)?;
+
+        Did::replace_keys_apply(wallet_handle, did)?;
+        Records::delete(wallet_handle, Did::PENDING_VERKEY_RECORD, did)?;
+
+        Ok(verkey)
+    }
+
+    /// Applies the temporary keys of an interrupted rotation if their verkey is the one on the ledger.
+    fn _apply_pending_keys(wallet_handle: IndyHandle, did: &str, ledger_verkey: &str) -> Result<String, ErrorCode> {
+        let pending: Option<Record<String>> = Records::get(wallet_handle, Did::PENDING_VERKEY_RECORD, did)?;
+
+        let verkey = match pending {
+            Some(record) => record.value,
+            None => {
+                warn!("No key rotation of {} is pending", did);
+                return Err(ErrorCode::WalletItemNotFound);
+            }
+        };
+
+        if !Did::_is_verkey(did, ledger_verkey, &verkey)? {
+            warn!("Verkey of {} on the ledger is not the pending verkey", did);
+            return Err(ErrorCode::WalletItemNotFound);
+        }
+
+        Did::replace_keys_apply(wallet_handle, did)?;
+        Records::delete(wallet_handle, Did::PENDING_VERKEY_RECORD, did)?;
+
+        Ok(verkey)
+    }
+
+    fn _set_pending_verkey(wallet_handle: IndyHandle, did: &str, verkey: &str) -> Result<(), ErrorCode> {
+        let pending: Option<Record<String>> = Records::get(wallet_handle, Did::PENDING_VERKEY_RECORD, did)?;
+
+        match pending {
+            Some(_) => Records::update(wallet_handle, Did::PENDING_VERKEY_RECORD, did, &verkey),
+            None => Records::add(wallet_handle, Did::PENDING_VERKEY_RECORD, did, &verkey, &Tags::new())
+        }
+    }
+
+    fn _confirm_verkey(pool_handle: IndyHandle, did: &str, verkey: &str, retries: ReadRetries) -> Result<(), ErrorCode> {
+        Fetch::read_until(retries, || {
+            let ledger_verkey = match Fetch::nym(pool_handle, Some(did), did) {
+                Ok(nym) => nym.and_then(|nym| nym.verkey),
+                Err(err) => {
+                    debug!("Failed to read the NYM of {}: {:?}", did, err);
+                    None
+                }
+            };
+
+            match ledger_verkey {
+                Some(ref ledger_verkey) if Did::_is_verkey(did, ledger_verkey, verkey)? => Ok(Some(())),
+                _ => Ok(None)
+            }
+        })
+    }
+
+    /// Whether a verkey returned by the ledger, full or abbreviated, is `verkey`.
+    fn _is_verkey(did: &str, ledger_verkey: &str, verkey: &str) -> Result<bool, ErrorCode> {
+        Ok(ledger_verkey == verkey || ledger_verkey == Did::abbreviate_verkey(did, verkey)?)
+    }
+
     /// Saves their DID for a pairwise connection in a secured Wallet,
     /// so that it can be used to verify transaction.
     ///
@@ -884,4 +1022,65 @@ impl Did {
 
         ErrorCode::from(unsafe { did::indy_abbreviate_verkey(command_handle, tgt_did.as_ptr(), verkey.as_ptr(), cb) })
     }
+
+    /// Updates a DID stored in the wallet to a fully qualified DID, or updates its DID method.
+    /// Keys, metadata and pairwise records bound to the DID are moved to the new DID.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - Wallet handle (created by Wallet::open).
+    /// * `did` - target DID stored in the wallet (qualified or unqualified).
+    /// * `method` - method to apply to the DID, e.g. "sov".
+    ///
+    /// # Returns
+    ///  * `full_qualified_did` - DID in the form `did:<method>:<did>`
+    pub fn qualify_did(wallet_handle: IndyHandle, did: &str, method: &str) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Did::_qualify_did(command_handle, wallet_handle, did, method, cb);
+
+        ResultHandler::one(err, receiver)
+    }
+
+    /// Updates a DID stored in the wallet to a fully qualified DID, or updates its DID method.
+    /// Keys, metadata and pairwise records bound to the DID are moved to the new DID.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - Wallet handle (created by Wallet::open).
+    /// * `did` - target DID stored in the wallet (qualified or unqualified).
+    /// * `method` - method to apply to the DID, e.g. "sov".
+    /// * `timeout` - the maximum time this function waits for a response
+    ///
+    /// # Returns
+    ///  * `full_qualified_did` - DID in the form `did:<method>:<did>`
+    pub fn qualify_did_timeout(wallet_handle: IndyHandle, did: &str, method: &str, timeout: Duration) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Did::_qualify_did(command_handle, wallet_handle, did, method, cb);
+
+        ResultHandler::one_timeout(err, receiver, timeout)
+    }
+
+    /// Updates a DID stored in the wallet to a fully qualified DID, or updates its DID method.
+    /// Keys, metadata and pairwise records bound to the DID are moved to the new DID.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - Wallet handle (created by Wallet::open).
+    /// * `did` - target DID stored in the wallet (qualified or unqualified).
+    /// * `method` - method to apply to the DID, e.g. "sov".
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn qualify_did_async<F: 'static>(wallet_handle: IndyHandle, did: &str, method: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode, String) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec_string(Box::new(closure));
+
+        Did::_qualify_did(command_handle, wallet_handle, did, method, cb)
+    }
+
+    fn _qualify_did(command_handle: IndyHandle, wallet_handle: IndyHandle, did: &str, method: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+        let did = c_str!(did);
+        let method = c_str!(method);
+
+        ErrorCode::from(unsafe { did::indy_qualify_did(command_handle, wallet_handle, did.as_ptr(), method.as_ptr(), cb) })
+    }
 }
@@ -0,0 +1,319 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json::Value;
+
+use qualifier::Qualifier;
+use utils::base58::Base58;
+use utils::fetch::{Fetch, NymData};
+
+const DID_METHOD: &str = "sov";
+const DID_CONTEXT: &str = "https://www.w3.org/ns/did/v1";
+const ED25519_CONTEXT: &str = "https://w3id.org/security/suites/ed25519-2018/v1";
+const ED25519_KEY_TYPE: &str = "Ed25519VerificationKey2018";
+const DID_DOC_CONTENT_TYPE: &str = "application/did+ld+json";
+
+/// Service types of an endpoint ATTRIB without `types`.
+const DEFAULT_SERVICE_TYPES: &[&str] = &["endpoint", "did-communication"];
+const DID_COMMUNICATION: &str = "did-communication";
+const DID_COMMUNICATION_ACCEPT: &str = "didcomm/aip2;env=rfc19";
+
+/// W3C DID Document of a `did:sov` DID.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocument {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    pub verification_method: Vec<VerificationMethod>,
+    /// Ids of the verification methods the DID authenticates with.
+    pub authentication: Vec<String>,
+    #[serde(default)]
+    pub service: Vec<Service>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationMethod {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub controller: String,
+    pub public_key_base58: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Service {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub service_endpoint: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient_keys: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub routing_keys: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidDocumentMetadata {
+    /// Sequence number of the NYM transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    /// Whether the NYM has no verkey anymore.
+    pub deactivated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolutionMetadata {
+    pub content_type: String,
+}
+
+/// Result of DidResolver::resolve.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidResolution {
+    pub did_document: DidDocument,
+    pub did_document_metadata: DidDocumentMetadata,
+    pub did_resolution_metadata: DidResolutionMetadata,
+}
+
+/// Resolves `did:sov` DIDs from the NYM and the endpoint ATTRIB on the ledger.
+pub struct DidResolver {}
+
+impl DidResolver {
+    /// Resolves a DID into a W3C DID Document.
+    ///
+    /// The verkey of the NYM becomes the verification method `#key-1`. The endpoint ATTRIB
+    /// becomes the services: `{"endpoint": {"endpoint": <url>, "routingKeys": [..], "types": [..]}}`
+    /// gives one service per type (`endpoint` and `did-communication` if `types` is missing),
+    /// the legacy `{"endpoint": {"ha": <address>}}` gives an `endpoint` service.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `did` - DID to resolve, `did:sov:<did>` or unqualified.
+    ///
+    /// # Returns
+    /// The DID Document with its metadata, `None` if the ledger has no NYM for the DID.
+    /// `CommonInvalidStructure` for a DID of another method.
+    pub fn resolve(pool_handle: IndyHandle, did: &str) -> Result<Option<DidResolution>, ErrorCode> {
+        if let Some(method) = Qualifier::method(did) {
+            if method != DID_METHOD {
+                warn!("Can not resolve DID method {}", method);
+                return Err(ErrorCode::CommonInvalidStructure);
+            }
+        }
+
+        let did = Qualifier::unqualify_did(did);
+
+        let nym = match Fetch::nym(pool_handle, None, &did)? {
+            Some(nym) => nym,
+            None => return Ok(None)
+        };
+
+        let endpoint = Fetch::attrib(pool_handle, None, &did, "endpoint")?;
+
+        DidResolver::document(&nym, endpoint.as_ref()).map(Some)
+    }
+
+    fn document(nym: &NymData, endpoint: Option<&Value>) -> Result<DidResolution, ErrorCode> {
+        let id = Qualifier::qualify_did(&nym.dest, DID_METHOD);
+
+        let verification_method = match nym.verkey {
+            Some(ref verkey) => vec![VerificationMethod {
+                id: format!("{}#key-1", id),
+                type_: ED25519_KEY_TYPE.to_string(),
+                controller: id.clone(),
+                public_key_base58: DidResolver::full_verkey(&nym.dest, verkey)?,
+            }],
+            None => Vec::new()
+        };
+
+        let authentication = verification_method.iter().map(|method| method.id.clone()).collect();
+
+        let service = match endpoint {
+            Some(endpoint) => DidResolver::services(&id, &authentication, endpoint)?,
+            None => Vec::new()
+        };
+
+        Ok(DidResolution {
+            did_document_metadata: DidDocumentMetadata {
+                version_id: nym.seq_no.map(|seq_no| seq_no.to_string()),
+                deactivated: verification_method.is_empty(),
+            },
+            did_document: DidDocument {
+                context: vec![DID_CONTEXT.to_string(), ED25519_CONTEXT.to_string()],
+                id,
+                verification_method,
+                authentication,
+                service,
+            },
+            did_resolution_metadata: DidResolutionMetadata { content_type: DID_DOC_CONTENT_TYPE.to_string() },
+        })
+    }
+
+    fn services(id: &str, recipient_keys: &Vec<String>, endpoint: &Value) -> Result<Vec<Service>, ErrorCode> {
+        let service = |type_: &str, service_endpoint: &str| Service {
+            id: format!("{}#{}", id, type_),
+            type_: type_.to_string(),
+            service_endpoint: service_endpoint.to_string(),
+            priority: None,
+            recipient_keys: None,
+            routing_keys: None,
+            accept: None,
+        };
+
+        if let Some(address) = endpoint["ha"].as_str() {
+            return Ok(vec![service("endpoint", address)]);
+        }
+
+        let url = match endpoint["endpoint"].as_str().or_else(|| endpoint.as_str()) {
+            Some(url) => url,
+            None => {
+                warn!("Endpoint ATTRIB of {} has no endpoint", id);
+                return Err(ErrorCode::CommonInvalidStructure);
+            }
+        };
+
+        let routing_keys = DidResolver::strings(&endpoint["routingKeys"])?.unwrap_or_default();
+
+        let types = DidResolver::strings(&endpoint["types"])?
+            .unwrap_or_else(|| DEFAULT_SERVICE_TYPES.iter().map(|type_| type_.to_string()).collect());
+
+        Ok(types.iter().map(|type_| {
+            let mut service = service(type_, url);
+
+            if type_ == DID_COMMUNICATION {
+                service.priority = Some(0);
+                service.recipient_keys = Some(recipient_keys.clone());
+                service.routing_keys = Some(routing_keys.clone());
+                service.accept = Some(vec![DID_COMMUNICATION_ACCEPT.to_string()]);
+            }
+
+            service
+        }).collect())
+    }
+
+    /// Expands an abbreviated verkey (`~` and the last 16 bytes) with the 16 bytes of the DID.
+    fn full_verkey(did: &str, verkey: &str) -> Result<String, ErrorCode> {
+        if !verkey.starts_with('~') {
+            return Ok(verkey.to_string());
+        }
+
+        match (Base58::decode(did), Base58::decode(&verkey[1..])) {
+            (Some(mut did), Some(key)) => {
+                did.extend(key);
+                Ok(Base58::encode(&did))
+            }
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+
+    fn strings(value: &Value) -> Result<Option<Vec<String>>, ErrorCode> {
+        match *value {
+            Value::Null => Ok(None),
+            Value::Array(ref values) => values.iter()
+                .map(|value| value.as_str().map(String::from).ok_or(ErrorCode::CommonInvalidStructure))
+                .collect::<Result<Vec<String>, ErrorCode>>()
+                .map(Some),
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use serde_json;
+
+    const DID: &str = "VsKV7grR1BUE29mG2Fm2kX";
+    const VERKEY: &str = "GjZWsBLgZCR18aL468JAT7w9CZRiBnpxUPPgyQxh4voa";
+    const VERKEY_ABV: &str = "~HYwqs2vrTc8Tn4uBV7NBTe";
+
+    fn nym(verkey: Option<&str>) -> NymData {
+        NymData { dest: DID.to_string(), role: None, verkey: verkey.map(String::from), seq_no: Some(15), txn_time: Some(1571000000) }
+    }
+
+    #[test]
+    fn full_verkey_works() {
+        assert_eq!(DidResolver::full_verkey(DID, VERKEY_ABV).unwrap(), VERKEY);
+        assert_eq!(DidResolver::full_verkey(DID, VERKEY).unwrap(), VERKEY);
+    }
+
+    #[test]
+    fn document_works() {
+        let endpoint = json!({"endpoint": "https://agent.example.com", "routingKeys": ["routing"]});
+
+        let resolution = DidResolver::document(&nym(Some(VERKEY_ABV)), Some(&endpoint)).unwrap();
+
+        assert_eq!(serde_json::to_value(&resolution).unwrap(), json!({
+            "didDocument": {
+                "@context": [DID_CONTEXT, ED25519_CONTEXT],
+                "id": "did:sov:VsKV7grR1BUE29mG2Fm2kX",
+                "verificationMethod": [{
+                    "id": "did:sov:VsKV7grR1BUE29mG2Fm2kX#key-1",
+                    "type": "Ed25519VerificationKey2018",
+                    "controller": "did:sov:VsKV7grR1BUE29mG2Fm2kX",
+                    "publicKeyBase58": VERKEY
+                }],
+                "authentication": ["did:sov:VsKV7grR1BUE29mG2Fm2kX#key-1"],
+                "service": [{
+                    "id": "did:sov:VsKV7grR1BUE29mG2Fm2kX#endpoint",
+                    "type": "endpoint",
+                    "serviceEndpoint": "https://agent.example.com"
+                }, {
+                    "id": "did:sov:VsKV7grR1BUE29mG2Fm2kX#did-communication",
+                    "type": "did-communication",
+                    "serviceEndpoint": "https://agent.example.com",
+                    "priority": 0,
+                    "recipientKeys": ["did:sov:VsKV7grR1BUE29mG2Fm2kX#key-1"],
+                    "routingKeys": ["routing"],
+                    "accept": ["didcomm/aip2;env=rfc19"]
+                }]
+            },
+            "didDocumentMetadata": {"versionId": "15", "deactivated": false},
+            "didResolutionMetadata": {"contentType": "application/did+ld+json"}
+        }));
+    }
+
+    #[test]
+    fn document_works_for_legacy_endpoint() {
+        let endpoint = json!({"ha": "127.0.0.1:9700", "verkey": VERKEY});
+
+        let service = DidResolver::document(&nym(Some(VERKEY)), Some(&endpoint)).unwrap().did_document.service;
+
+        assert_eq!(service.len(), 1);
+        assert_eq!(service[0].type_, "endpoint");
+        assert_eq!(service[0].service_endpoint, "127.0.0.1:9700");
+    }
+
+    #[test]
+    fn document_works_for_explicit_types() {
+        let endpoint = json!({"endpoint": "https://agent.example.com", "types": ["DIDComm"]});
+
+        let service = DidResolver::document(&nym(Some(VERKEY)), Some(&endpoint)).unwrap().did_document.service;
+
+        assert_eq!(service.len(), 1);
+        assert_eq!(service[0].id, "did:sov:VsKV7grR1BUE29mG2Fm2kX#DIDComm");
+    }
+
+    #[test]
+    fn document_works_for_deactivated_did() {
+        let resolution = DidResolver::document(&nym(None), None).unwrap();
+
+        assert!(resolution.did_document.verification_method.is_empty());
+        assert!(resolution.did_document.authentication.is_empty());
+        assert!(resolution.did_document_metadata.deactivated);
+    }
+
+    #[test]
+    fn document_fails_for_endpoint_without_url() {
+        assert_eq!(DidResolver::document(&nym(Some(VERKEY)), Some(&json!({"routingKeys": []}))).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
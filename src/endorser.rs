@@ -0,0 +1,111 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json;
+use serde_json::Value;
+
+use ledger::Ledger;
+use utils::reply::ReplyHandler;
+
+/// Ledger writes of authors without write permission, submitted by an endorser.
+///
+/// The author prepares and signs the request with `Endorser::author_request` and passes it to
+/// the endorser, which signs and submits it with `Endorser::endorse`.
+pub struct Endorser {}
+
+impl Endorser {
+    /// Sets the endorser of a request and signs it by the author.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) of the author.
+    /// * `author_did` - DID of the author, the submitter of the request.
+    /// * `endorser_did` - DID of the endorser that will submit the request.
+    /// * `request_json` - request json built by the author.
+    ///
+    /// # Returns
+    /// The signed request json to pass to the endorser.
+    /// `CommonInvalidStructure` if the author is not the submitter of the request.
+    pub fn author_request(wallet_handle: IndyHandle, author_did: &str, endorser_did: &str, request_json: &str) -> Result<String, ErrorCode> {
+        let request = Endorser::parse(request_json)?;
+
+        if request["identifier"].as_str() != Some(author_did) {
+            warn!("{} is not the submitter of the request", author_did);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        let request_json = Ledger::append_request_endorser(request_json, endorser_did)?;
+
+        Ledger::sign_request(wallet_handle, author_did, &request_json)
+    }
+
+    /// Signs a request of an author as its endorser and submits it.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) of the endorser.
+    /// * `endorser_did` - DID of the endorser, as set by the author.
+    /// * `request_json` - request json signed by the author (see Endorser::author_request).
+    ///
+    /// # Returns
+    /// The reply of the ledger. `CommonInvalidStructure` if the request is not signed by its
+    /// author or has another endorser and `LedgerInvalidTransaction` if the ledger rejects it.
+    pub fn endorse(pool_handle: IndyHandle, wallet_handle: IndyHandle, endorser_did: &str, request_json: &str) -> Result<String, ErrorCode> {
+        let request = Endorser::parse(request_json)?;
+
+        if request["endorser"].as_str() != Some(endorser_did) {
+            warn!("Request is not to be endorsed by {}", endorser_did);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        if !Endorser::is_signed_by_author(&request) {
+            warn!("Request is not signed by its author");
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        let request_json = Ledger::multi_sign_request(wallet_handle, endorser_did, request_json)?;
+
+        let response = Ledger::submit_request(pool_handle, &request_json)?;
+        ReplyHandler::check(&response)?;
+
+        Ok(response)
+    }
+
+    fn is_signed_by_author(request: &Value) -> bool {
+        match request["identifier"].as_str() {
+            Some(author_did) => request.get("signature").is_some() || request["signatures"].get(author_did).is_some(),
+            None => false
+        }
+    }
+
+    fn parse(request_json: &str) -> Result<Value, ErrorCode> {
+        serde_json::from_str(request_json).map_err(|_| ErrorCode::CommonInvalidStructure)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn is_signed_by_author_works() {
+        assert!(Endorser::is_signed_by_author(&json!({"identifier": "Author", "signature": "signature"})));
+        assert!(Endorser::is_signed_by_author(&json!({"identifier": "Author", "signatures": {"Author": "signature"}})));
+
+        assert!(!Endorser::is_signed_by_author(&json!({"identifier": "Author"})));
+        assert!(!Endorser::is_signed_by_author(&json!({"identifier": "Author", "signatures": {"Endorser": "signature"}})));
+        assert!(!Endorser::is_signed_by_author(&json!({"signature": "signature"})));
+    }
+
+    #[test]
+    fn endorse_fails_for_other_endorser() {
+        let request = json!({"identifier": "Author", "endorser": "Endorser", "signature": "signature"}).to_string();
+
+        assert_eq!(Endorser::endorse(1, 1, "Other", &request).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn endorse_fails_for_unsigned_request() {
+        let request = json!({"identifier": "Author", "endorser": "Endorser"}).to_string();
+
+        assert_eq!(Endorser::endorse(1, 1, "Endorser", &request).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
@@ -0,0 +1,150 @@
+use {ErrorCode, IndyHandle};
+
+use std::time::Duration;
+
+use serde_json::Value;
+
+use anoncreds::Issuer;
+use ledger::Ledger;
+use utils::fetch::Fetch;
+use utils::reply::ReplyHandler;
+
+/// Schema and credential definition as read back from the ledger.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublishedCredentialDef {
+    pub schema_id: String,
+    pub schema_json: String,
+    pub cred_def_id: String,
+    pub cred_def_json: String,
+}
+
+/// Schema and credential definition to publish with `IssuerSetup::publish`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CredentialDefConfig<'a> {
+    /// Name of the schema.
+    pub name: &'a str,
+    /// Version of the schema.
+    pub version: &'a str,
+    /// Json array of schema attribute names (at most 125).
+    pub attrs: &'a str,
+    /// Distinguishes credential definitions of the same issuer and schema.
+    pub tag: &'a str,
+    /// Credential definition type (optional, 'CL' by default).
+    pub signature_type: Option<&'a str>,
+    /// Type-specific configuration of credential definition (see Issuer::create_and_store_credential_def).
+    pub config_json: &'a str,
+}
+
+/// How a written transaction is read back, e.g. by `IssuerSetup::publish_with_retries`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReadRetries {
+    /// Number of reads of a written transaction before giving up (at least one read is made).
+    pub attempts: u32,
+    /// The time to wait between two reads.
+    pub delay: Duration,
+}
+
+impl Default for ReadRetries {
+    fn default() -> ReadRetries {
+        ReadRetries {
+            attempts: IssuerSetup::READ_ATTEMPTS,
+            delay: Duration::from_millis(IssuerSetup::READ_DELAY_MS),
+        }
+    }
+}
+
+pub struct IssuerSetup {}
+
+impl IssuerSetup {
+    /// Number of reads `IssuerSetup::publish` makes before giving up on a written transaction.
+    pub const READ_ATTEMPTS: u32 = 10;
+    /// Delay in milliseconds between two reads of `IssuerSetup::publish`.
+    pub const READ_DELAY_MS: u64 = 500;
+
+    /// Creates a schema and a credential definition for it and publishes both on the ledger.
+    ///
+    /// Every write reply is checked. After each write the transaction is read back until
+    /// the pool returns it (the credential definition needs the seqNo of the schema), see
+    /// `IssuerSetup::READ_ATTEMPTS` and `IssuerSetup::READ_DELAY_MS`.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `issuer_did` - DID of the issuer, it signs both transactions.
+    /// * `config` - the schema and credential definition to publish.
+    ///
+    /// # Returns
+    /// The schema and credential definition returned by the ledger.
+    /// `PoolLedgerTimeout` if a written transaction did not become visible.
+    pub fn publish(pool_handle: IndyHandle, wallet_handle: IndyHandle, issuer_did: &str, config: &CredentialDefConfig) -> Result<PublishedCredentialDef, ErrorCode> {
+        IssuerSetup::publish_with_retries(pool_handle, wallet_handle, issuer_did, config, ReadRetries::default())
+    }
+
+    /// Creates a schema and a credential definition for it and publishes both on the ledger.
+    ///
+    /// Same as `IssuerSetup::publish`, with the given number of reads and delay between them.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `issuer_did` - DID of the issuer, it signs both transactions.
+    /// * `config` - the schema and credential definition to publish.
+    /// * `retries` - how written transactions are read back.
+    ///
+    /// # Returns
+    /// The schema and credential definition returned by the ledger.
+    /// `PoolLedgerTimeout` if a written transaction did not become visible.
+    pub fn publish_with_retries(pool_handle: IndyHandle, wallet_handle: IndyHandle, issuer_did: &str, config: &CredentialDefConfig, retries: ReadRetries) -> Result<PublishedCredentialDef, ErrorCode> {
+        let (schema_id, schema_json) = Issuer::create_schema(issuer_did, config.name, config.version, config.attrs)?;
+
+        let request = Ledger::build_schema_request(issuer_did, &schema_json)?;
+        let response = Ledger::sign_and_submit_request(pool_handle, wallet_handle, issuer_did, &request)?;
+        ReplyHandler::check(&response)?;
+
+        let request = Ledger::build_get_schema_request(Some(issuer_did), &schema_id)?;
+        let response = IssuerSetup::read_until_visible(pool_handle, &request, retries)?;
+        let (schema_id, schema_json) = Ledger::parse_get_schema_response(&response)?;
+
+        let (cred_def_id, cred_def_json) = Issuer::create_and_store_credential_def(wallet_handle, issuer_did, &schema_json, config.tag, config.signature_type, config.config_json)?;
+
+        let request = Ledger::build_cred_def_request(issuer_did, &cred_def_json)?;
+        let response = Ledger::sign_and_submit_request(pool_handle, wallet_handle, issuer_did, &request)?;
+        ReplyHandler::check(&response)?;
+
+        let request = Ledger::build_get_cred_def_request(Some(issuer_did), &cred_def_id)?;
+        let response = IssuerSetup::read_until_visible(pool_handle, &request, retries)?;
+        let (cred_def_id, cred_def_json) = Ledger::parse_get_cred_def_response(&response)?;
+
+        Ok(PublishedCredentialDef { schema_id, schema_json, cred_def_id, cred_def_json })
+    }
+
+    fn read_until_visible(pool_handle: IndyHandle, request: &str, retries: ReadRetries) -> Result<String, ErrorCode> {
+        Fetch::read_until(retries, || {
+            let response = Ledger::submit_request(pool_handle, request)?;
+
+            if IssuerSetup::is_visible(&ReplyHandler::check(&response)?) {
+                Ok(Some(response))
+            } else {
+                Ok(None)
+            }
+        })
+    }
+
+    /// A read reply for a transaction that is not written (yet) has no seqNo.
+    fn is_visible(reply: &Value) -> bool {
+        !reply["result"]["seqNo"].is_null()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn is_visible_works() {
+        assert!(IssuerSetup::is_visible(&json!({"op": "REPLY", "result": {"seqNo": 10, "data": {}}})));
+        assert!(!IssuerSetup::is_visible(&json!({"op": "REPLY", "result": {"seqNo": null, "data": null}})));
+        assert!(!IssuerSetup::is_visible(&json!({"op": "REPLY", "result": {}})));
+    }
+
+}
@@ -10,6 +10,8 @@ use native::{ResponseEmptyCB,
           ResponseStringStringCB,
           ResponseStringStringU64CB};
 
+use state_proof;
+use state_proof::StateProofParser;
 use utils::results::ResultHandler;
 use utils::callbacks::ClosureHandler;
 
@@ -326,6 +328,76 @@ impl Ledger {
         ErrorCode::from(unsafe { ledger::indy_multi_sign_request(command_handle, wallet_handle, submitter_did.as_ptr(), request_json.as_ptr(), cb) })
     }
 
+    /// Appends an endorser to an existing request.
+    ///
+    /// The author of the request is still the `submitter_did` of the request. The endorser is the DID
+    /// that submits the request to the ledger, it must be on the ledger with the endorser role.
+    /// The author signs the request with the endorser set (see Ledger::sign_request), the endorser
+    /// signs it then with Ledger::multi_sign_request and submits it.
+    ///
+    /// # Arguments
+    /// * `request_json` - original request data json.
+    /// * `endorser_did` - DID of the endorser that will submit the transaction.
+    ///
+    /// # Returns
+    /// Updated request result as json.
+    pub fn append_request_endorser(request_json: &str, endorser_did: &str) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Ledger::_append_request_endorser(command_handle, request_json, endorser_did, cb);
+
+        ResultHandler::one(err, receiver)
+    }
+
+    /// Appends an endorser to an existing request.
+    ///
+    /// The author of the request is still the `submitter_did` of the request. The endorser is the DID
+    /// that submits the request to the ledger, it must be on the ledger with the endorser role.
+    /// The author signs the request with the endorser set (see Ledger::sign_request), the endorser
+    /// signs it then with Ledger::multi_sign_request and submits it.
+    ///
+    /// # Arguments
+    /// * `request_json` - original request data json.
+    /// * `endorser_did` - DID of the endorser that will submit the transaction.
+    /// * `timeout` - the maximum time this function waits for a response
+    ///
+    /// # Returns
+    /// Updated request result as json.
+    pub fn append_request_endorser_timeout(request_json: &str, endorser_did: &str, timeout: Duration) -> Result<String, ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec_string();
+
+        let err = Ledger::_append_request_endorser(command_handle, request_json, endorser_did, cb);
+
+        ResultHandler::one_timeout(err, receiver, timeout)
+    }
+
+    /// Appends an endorser to an existing request.
+    ///
+    /// The author of the request is still the `submitter_did` of the request. The endorser is the DID
+    /// that submits the request to the ledger, it must be on the ledger with the endorser role.
+    /// The author signs the request with the endorser set (see Ledger::sign_request), the endorser
+    /// signs it then with Ledger::multi_sign_request and submits it.
+    ///
+    /// # Arguments
+    /// * `request_json` - original request data json.
+    /// * `endorser_did` - DID of the endorser that will submit the transaction.
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn append_request_endorser_async<F: 'static>(request_json: &str, endorser_did: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode, String) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec_string(Box::new(closure));
+
+        Ledger::_append_request_endorser(command_handle, request_json, endorser_did, cb)
+    }
+
+    fn _append_request_endorser(command_handle: IndyHandle, request_json: &str, endorser_did: &str, cb: Option<ResponseStringCB>) -> ErrorCode {
+        let request_json = c_str!(request_json);
+        let endorser_did = c_str!(endorser_did);
+
+        ErrorCode::from(unsafe { ledger::indy_append_request_endorser(command_handle, request_json.as_ptr(), endorser_did.as_ptr(), cb) })
+    }
+
     /// Builds a request to get a DDO.
     ///
     /// # Arguments
@@ -2245,6 +2317,44 @@ impl Ledger {
           ledger::indy_register_transaction_parser_for_sp(command_handle, txn_type.as_ptr(), parser, free, cb)
         })
     }
+
+    /// Registers a state proof parser implemented in Rust for replies to `txn_type` requests.
+    ///
+    /// The crate serializes the `ParsedSP` entries returned by `T` and frees them
+    /// once libindy has verified them.
+    ///
+    /// # Arguments
+    /// * `txn_type` - type of transaction to apply the parser to.
+    pub fn register_state_proof_parser<T: StateProofParser>(txn_type: &str) -> Result<(), ErrorCode> {
+        Ledger::register_transaction_parser_for_sp(txn_type, Some(state_proof::parse::<T>), Some(state_proof::free))
+    }
+
+    /// Registers a state proof parser implemented in Rust for replies to `txn_type` requests.
+    ///
+    /// The crate serializes the `ParsedSP` entries returned by `T` and frees them
+    /// once libindy has verified them.
+    ///
+    /// # Arguments
+    /// * `txn_type` - type of transaction to apply the parser to.
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn register_state_proof_parser_timeout<T: StateProofParser>(txn_type: &str, timeout: Duration) -> Result<(), ErrorCode> {
+        Ledger::register_transaction_parser_for_sp_timeout(txn_type, Some(state_proof::parse::<T>), Some(state_proof::free), timeout)
+    }
+
+    /// Registers a state proof parser implemented in Rust for replies to `txn_type` requests.
+    ///
+    /// The crate serializes the `ParsedSP` entries returned by `T` and frees them
+    /// once libindy has verified them.
+    ///
+    /// # Arguments
+    /// * `txn_type` - type of transaction to apply the parser to.
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn register_state_proof_parser_async<T: StateProofParser, F: 'static>(txn_type: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode) + Send {
+        Ledger::register_transaction_parser_for_sp_async(txn_type, Some(state_proof::parse::<T>), Some(state_proof::free), closure)
+    }
 }
 
 
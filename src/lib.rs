@@ -3,21 +3,48 @@ extern crate lazy_static;
 #[macro_use]
 extern crate log;
 
+extern crate num_bigint;
 extern crate num_traits;
 #[macro_use]
 extern crate num_derive;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+extern crate sha2;
+#[cfg(feature = "sqlite_storage")]
+extern crate rusqlite;
 
 #[macro_use]
 mod macros;
 
 pub mod anoncreds;
+pub mod auto_prover;
 pub mod blob_storage;
+pub mod cache;
+pub mod connection;
+pub mod credential_exchange;
+pub mod credential_values;
 pub mod crypto;
 pub mod did;
+pub mod did_doc;
+pub mod endorser;
+pub mod issuer_setup;
 pub mod ledger;
+pub mod multi_sig;
+pub mod onboarding;
 pub mod payments;
+pub mod payment_method;
 pub mod pairwise;
 pub mod pool;
+pub mod proof;
+pub mod qualifier;
+pub mod revocation;
+pub mod revocation_state;
+pub mod secure_channel;
+pub mod state_proof;
+pub mod storage;
 pub mod wallet;
 pub mod utils;
 pub mod native;
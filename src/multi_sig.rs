@@ -0,0 +1,303 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json;
+use serde_json::{Map, Value};
+
+use ledger::Ledger;
+use utils::reply::ReplyHandler;
+
+/// Ledger request that has to be signed by several DIDs, e.g. by trustees.
+///
+/// Every signer signs its own copy (see `MultiSigRequest::sign`), possibly in another process,
+/// and the copies are merged back with `MultiSigRequest::merge`. `MultiSigRequest::to_json`
+/// and `MultiSigRequest::from_json` give the form to pass between the processes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiSigRequest {
+    /// The request with the signatures collected so far.
+    request: Value,
+    /// DIDs whose signatures count for the quorum.
+    signers: Vec<String>,
+    /// Number of signatures of `signers` needed to submit the request.
+    quorum: usize,
+}
+
+impl MultiSigRequest {
+    /// Starts collecting signatures for a request.
+    ///
+    /// # Arguments
+    /// * `request_json` - request json (e.g. built by Ledger::build_pool_upgrade_request).
+    /// * `signers` - DIDs that may sign the request.
+    /// * `quorum` - number of signatures of `signers` needed to submit the request.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the request is not a json object or the quorum can not be reached.
+    pub fn new(request_json: &str, signers: &[&str], quorum: usize) -> Result<MultiSigRequest, ErrorCode> {
+        let request: Value = serde_json::from_str(request_json).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        if !request.is_object() || quorum == 0 || quorum > signers.len() {
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        Ok(MultiSigRequest { request, signers: signers.iter().map(|signer| signer.to_string()).collect(), quorum })
+    }
+
+    pub fn from_json(multi_sig_request_json: &str) -> Result<MultiSigRequest, ErrorCode> {
+        serde_json::from_str(multi_sig_request_json).map_err(|_| ErrorCode::CommonInvalidStructure)
+    }
+
+    pub fn to_json(&self) -> Result<String, ErrorCode> {
+        serde_json::to_string(self).map_err(|_| ErrorCode::CommonInvalidState)
+    }
+
+    /// Returns the request json with the signatures collected so far.
+    pub fn request_json(&self) -> String {
+        self.request.to_string()
+    }
+
+    /// Adds the signature of a signer (see Ledger::multi_sign_request).
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `signer_did` - one of the signers, stored in the wallet.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the DID is not a signer of the request.
+    pub fn sign(&mut self, wallet_handle: IndyHandle, signer_did: &str) -> Result<(), ErrorCode> {
+        if !self.is_signer(signer_did) {
+            warn!("{} is not a signer of the request", signer_did);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        let signed = Ledger::multi_sign_request(wallet_handle, signer_did, &self.request.to_string())?;
+        let signed: Value = serde_json::from_str(&signed).map_err(|_| ErrorCode::CommonInvalidState)?;
+
+        self.merge_signatures(&signed)
+    }
+
+    /// Adds the signatures of another copy of the request.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the copy is for another request, signers or quorum or has
+    /// another signature of a signer than the request.
+    pub fn merge(&mut self, other: &MultiSigRequest) -> Result<(), ErrorCode> {
+        if self.signers != other.signers || self.quorum != other.quorum {
+            warn!("Can not merge signatures of requests with different signers");
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        self.merge_signatures(&other.request)
+    }
+
+    /// DIDs of the signers that signed the request.
+    pub fn signed_by(&self) -> Vec<String> {
+        let signatures = MultiSigRequest::signatures(&self.request);
+
+        self.signers.iter().filter(|signer| signatures.contains_key(*signer)).cloned().collect()
+    }
+
+    /// DIDs of the signers that did not sign the request yet.
+    pub fn missing_signers(&self) -> Vec<String> {
+        let signatures = MultiSigRequest::signatures(&self.request);
+
+        self.signers.iter().filter(|signer| !signatures.contains_key(*signer)).cloned().collect()
+    }
+
+    /// Whether enough signers signed the request.
+    pub fn has_quorum(&self) -> bool {
+        self.signed_by().len() >= self.quorum
+    }
+
+    /// Submits the request once the quorum is reached.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    ///
+    /// # Returns
+    /// The reply of the ledger. `CommonInvalidState` if the quorum is not reached and
+    /// `LedgerInvalidTransaction` if the ledger rejects the request.
+    pub fn submit(&self, pool_handle: IndyHandle) -> Result<String, ErrorCode> {
+        if !self.has_quorum() {
+            warn!("Request is signed by {} of {} signers", self.signed_by().len(), self.quorum);
+            return Err(ErrorCode::CommonInvalidState);
+        }
+
+        let response = Ledger::submit_request(pool_handle, &self.request.to_string())?;
+        ReplyHandler::check(&response)?;
+
+        Ok(response)
+    }
+
+    /// Copies the signatures of the signers in `signed` into the request, if both are the same
+    /// request. Signatures of other DIDs are dropped.
+    fn merge_signatures(&mut self, signed: &Value) -> Result<(), ErrorCode> {
+        if MultiSigRequest::unsigned(&self.request) != MultiSigRequest::unsigned(signed) {
+            warn!("Can not merge signatures of different requests");
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        let mut signatures: Map<String, Value> = MultiSigRequest::signatures(&self.request).into_iter()
+            .filter(|(did, _)| self.is_signer(did))
+            .collect();
+
+        for (did, signature) in MultiSigRequest::signatures(signed) {
+            if !self.is_signer(&did) {
+                continue;
+            }
+
+            if let Some(existing) = signatures.get(&did) {
+                if *existing != signature {
+                    warn!("Can not merge another signature of {}", did);
+                    return Err(ErrorCode::CommonInvalidStructure);
+                }
+                continue;
+            }
+
+            signatures.insert(did, signature);
+        }
+
+        let request = self.request.as_object_mut().ok_or(ErrorCode::CommonInvalidState)?;
+        request.remove("signature");
+        request.insert("signatures".to_string(), Value::Object(signatures));
+
+        Ok(())
+    }
+
+    fn is_signer(&self, did: &str) -> bool {
+        self.signers.iter().any(|signer| signer == did)
+    }
+
+    /// Signatures by DID, including a single `signature` of the submitter.
+    fn signatures(request: &Value) -> Map<String, Value> {
+        let mut signatures = request["signatures"].as_object().cloned().unwrap_or_default();
+
+        if let (Some(identifier), Some(signature)) = (request["identifier"].as_str(), request.get("signature")) {
+            signatures.insert(identifier.to_string(), signature.clone());
+        }
+
+        signatures
+    }
+
+    fn unsigned(request: &Value) -> Value {
+        let mut request = request.clone();
+
+        if let Some(request) = request.as_object_mut() {
+            request.remove("signature");
+            request.remove("signatures");
+        }
+
+        request
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REQUEST: &str = r#"{"reqId": 1, "identifier": "Trustee1", "operation": {"type": "111"}, "protocolVersion": 2}"#;
+
+    fn signed(request: &MultiSigRequest, signer: &str) -> MultiSigRequest {
+        let mut signed = request.clone();
+        signed.request["signatures"] = json!({});
+        signed.request["signatures"][signer] = json!(format!("signature of {}", signer));
+        signed
+    }
+
+    #[test]
+    fn new_checks_quorum() {
+        assert_eq!(MultiSigRequest::new(REQUEST, &["Trustee1"], 2).unwrap_err(), ErrorCode::CommonInvalidStructure);
+        assert_eq!(MultiSigRequest::new(REQUEST, &["Trustee1"], 0).unwrap_err(), ErrorCode::CommonInvalidStructure);
+        assert_eq!(MultiSigRequest::new("[]", &["Trustee1"], 1).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn merge_collects_signatures_until_quorum() {
+        let mut request = MultiSigRequest::new(REQUEST, &["Trustee1", "Trustee2", "Trustee3"], 2).unwrap();
+        assert!(!request.has_quorum());
+
+        request.merge(&signed(&request, "Trustee2")).unwrap();
+        assert_eq!(request.signed_by(), vec!["Trustee2"]);
+        assert!(!request.has_quorum());
+
+        let other = MultiSigRequest::from_json(&signed(&request, "Trustee3").to_json().unwrap()).unwrap();
+        request.merge(&other).unwrap();
+
+        assert_eq!(request.signed_by(), vec!["Trustee2", "Trustee3"]);
+        assert_eq!(request.missing_signers(), vec!["Trustee1"]);
+        assert!(request.has_quorum());
+
+        let request: Value = serde_json::from_str(&request.request_json()).unwrap();
+        assert_eq!(request["signatures"], json!({"Trustee2": "signature of Trustee2", "Trustee3": "signature of Trustee3"}));
+    }
+
+    #[test]
+    fn merge_ignores_signatures_of_others() {
+        let mut request = MultiSigRequest::new(REQUEST, &["Trustee1", "Trustee2"], 1).unwrap();
+
+        request.merge(&signed(&request, "Steward1")).unwrap();
+
+        assert!(request.signed_by().is_empty());
+        assert!(!request.has_quorum());
+
+        let request: Value = serde_json::from_str(&request.request_json()).unwrap();
+        assert!(request["signatures"].get("Steward1").is_none());
+    }
+
+    #[test]
+    fn merge_drops_single_signature_of_other_submitter() {
+        let mut request = MultiSigRequest::new(REQUEST, &["Trustee2"], 1).unwrap();
+        request.request["signature"] = json!("signature of Trustee1");
+
+        request.merge(&signed(&request, "Trustee2")).unwrap();
+
+        let request: Value = serde_json::from_str(&request.request_json()).unwrap();
+        assert_eq!(request["signatures"], json!({"Trustee2": "signature of Trustee2"}));
+        assert!(request.get("signature").is_none());
+    }
+
+    #[test]
+    fn merge_keeps_existing_signature() {
+        let mut request = MultiSigRequest::new(REQUEST, &["Trustee1", "Trustee2"], 2).unwrap();
+        request.merge(&signed(&request, "Trustee1")).unwrap();
+
+        request.merge(&signed(&request, "Trustee1")).unwrap();
+        assert_eq!(request.signed_by(), vec!["Trustee1"]);
+
+        let mut other = signed(&request, "Trustee1");
+        other.request["signatures"]["Trustee1"] = json!("forged");
+
+        assert_eq!(request.merge(&other).unwrap_err(), ErrorCode::CommonInvalidStructure);
+        assert_eq!(request.request["signatures"]["Trustee1"], "signature of Trustee1");
+    }
+
+    #[test]
+    fn merge_counts_single_signature_of_submitter() {
+        let mut request = MultiSigRequest::new(REQUEST, &["Trustee1", "Trustee2"], 2).unwrap();
+        let mut other = request.clone();
+        other.request["signature"] = json!("signature of Trustee1");
+
+        request.merge(&other).unwrap();
+        request.merge(&signed(&request, "Trustee2")).unwrap();
+
+        assert_eq!(request.signed_by(), vec!["Trustee1", "Trustee2"]);
+        assert!(request.request.get("signature").is_none());
+    }
+
+    #[test]
+    fn merge_fails_for_other_request() {
+        let mut request = MultiSigRequest::new(REQUEST, &["Trustee1"], 1).unwrap();
+        let mut other = signed(&request, "Trustee1");
+        other.request["reqId"] = json!(2);
+
+        assert_eq!(request.merge(&other).unwrap_err(), ErrorCode::CommonInvalidStructure);
+
+        let other = MultiSigRequest::new(REQUEST, &["Trustee1", "Trustee2"], 1).unwrap();
+        assert_eq!(request.merge(&other).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn submit_fails_without_quorum() {
+        let request = MultiSigRequest::new(REQUEST, &["Trustee1"], 1).unwrap();
+
+        assert_eq!(request.submit(1).unwrap_err(), ErrorCode::CommonInvalidState);
+    }
+}
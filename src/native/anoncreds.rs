@@ -20,6 +20,17 @@ extern {
                                                        config_json: CString,
                                                        cb: Option<ResponseStringStringCB>) -> Error;
     #[no_mangle]
+    pub fn indy_issuer_rotate_credential_def_start(command_handle: Handle,
+                                                   wallet_handle: Handle,
+                                                   cred_def_id: CString,
+                                                   config_json: CString,
+                                                   cb: Option<ResponseStringCB>) -> Error;
+    #[no_mangle]
+    pub fn indy_issuer_rotate_credential_def_apply(command_handle: Handle,
+                                                   wallet_handle: Handle,
+                                                   cred_def_id: CString,
+                                                   cb: Option<ResponseEmptyCB>) -> Error;
+    #[no_mangle]
     pub fn indy_issuer_create_and_store_revoc_reg(command_handle: Handle,
                                                   wallet_handle: Handle,
                                                   issuer_did: CString,
@@ -158,5 +169,9 @@ extern {
                                         timestamp: u64,
                                         cred_rev_id: CString,
                                         cb: Option<ResponseStringCB>) -> Error;
+    #[no_mangle]
+    pub fn indy_to_unqualified(command_handle: Handle,
+                               entity: CString,
+                               cb: Option<ResponseStringCB>) -> Error;
 }
 
@@ -81,4 +81,11 @@ extern {
                                   did: CString,
                                   full_verkey: CString,
                                   cb: Option<ResponseStringCB>) -> Error;
+
+    #[no_mangle]
+    pub fn indy_qualify_did(command_handle: Handle,
+                            wallet_handle: Handle,
+                            did: CString,
+                            method: CString,
+                            cb: Option<ResponseStringCB>) -> Error;
 }
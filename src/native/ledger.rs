@@ -35,6 +35,11 @@ extern {
                                    request_json: CString,
                                    cb: Option<ResponseStringCB>) -> Error;
     #[no_mangle]
+    pub fn indy_append_request_endorser(command_handle: Handle,
+                                        request_json: CString,
+                                        endorser_did: CString,
+                                        cb: Option<ResponseStringCB>) -> Error;
+    #[no_mangle]
     pub fn indy_build_get_ddo_request(command_handle: Handle,
                                       submitter_did: CString,
                                       target_did: CString,
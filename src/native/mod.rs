@@ -1,5 +1,6 @@
 pub mod anoncreds;
 pub mod blob_storage;
+pub mod cache;
 pub mod crypto;
 pub mod did;
 pub mod ledger;
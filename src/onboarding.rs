@@ -0,0 +1,180 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json::Value;
+
+use did::Did;
+use ledger::Ledger;
+use utils::fetch::{Fetch, NymData};
+use utils::reply::ReplyHandler;
+
+/// Role of a NYM on the ledger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NymRole {
+    Trustee,
+    Steward,
+    /// Named TRUST_ANCHOR in NYM requests.
+    Endorser,
+    /// Common user without a role.
+    User,
+}
+
+impl NymRole {
+    /// The role as accepted by Ledger::build_nym_request.
+    pub fn request_role(&self) -> &'static str {
+        match *self {
+            NymRole::Trustee => "TRUSTEE",
+            NymRole::Steward => "STEWARD",
+            NymRole::Endorser => "TRUST_ANCHOR",
+            NymRole::User => "",
+        }
+    }
+
+    /// The role as returned by GET_NYM, `None` for a user.
+    pub fn ledger_role(&self) -> Option<&'static str> {
+        match *self {
+            NymRole::Trustee => Some("0"),
+            NymRole::Steward => Some("2"),
+            NymRole::Endorser => Some("101"),
+            NymRole::User => None,
+        }
+    }
+}
+
+/// Endpoint ATTRIB of an onboarded DID.
+///
+/// The ATTRIB is signed by the onboarded DID itself, so `wallet_handle` is the wallet the DID
+/// was created in (it may be the wallet of the submitter).
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointAttrib {
+    pub wallet_handle: IndyHandle,
+    /// Address of the endpoint, e.g. `127.0.0.1:9700`.
+    pub address: String,
+    /// Key for the transport (optional).
+    pub transport_key: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerUpdate {
+    /// The ledger already had the data.
+    Unchanged,
+    Written,
+}
+
+/// Result of Onboarding::onboard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnboardedDid {
+    pub did: String,
+    pub nym: LedgerUpdate,
+    /// `None` if no endpoint was given.
+    pub endpoint: Option<LedgerUpdate>,
+}
+
+pub struct Onboarding {}
+
+impl Onboarding {
+    /// Writes the NYM of a new DID and optionally its endpoint ATTRIB.
+    ///
+    /// Each transaction is only written if the ledger does not have it yet: the NYM if GET_NYM
+    /// returns another verkey or role, the ATTRIB if GET_ATTRIB returns another endpoint.
+    /// Onboarding a DID again is therefore a no-op. Every write reply is checked.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open) of the submitter.
+    /// * `submitter_did` - trustee or steward DID that signs the NYM.
+    /// * `did` - the DID to onboard (created by Did::new).
+    /// * `verkey` - verkey of the DID.
+    /// * `alias` - alias of the NYM (optional).
+    /// * `role` - role of the NYM.
+    /// * `endpoint` - endpoint ATTRIB of the DID (optional).
+    ///
+    /// # Returns
+    /// What was written. `LedgerInvalidTransaction` if the ledger rejects a write, e.g. because
+    /// the DID is owned by someone else or the submitter may not grant the role.
+    pub fn onboard(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, did: &str, verkey: &str, alias: Option<&str>, role: NymRole, endpoint: Option<&EndpointAttrib>) -> Result<OnboardedDid, ErrorCode> {
+        let nym = match Fetch::nym(pool_handle, Some(submitter_did), did)? {
+            Some(ref nym) if Onboarding::nym_matches(nym, verkey, role)? => LedgerUpdate::Unchanged,
+            _ => {
+                let request = Ledger::build_nym_request(submitter_did, did, Some(verkey), alias, Some(role.request_role()))?;
+                Onboarding::write(pool_handle, wallet_handle, submitter_did, &request)?;
+                LedgerUpdate::Written
+            }
+        };
+
+        let endpoint = match endpoint {
+            Some(endpoint) => Some(Onboarding::set_endpoint(pool_handle, did, endpoint)?),
+            None => None
+        };
+
+        Ok(OnboardedDid { did: did.to_string(), nym, endpoint })
+    }
+
+    fn set_endpoint(pool_handle: IndyHandle, did: &str, endpoint: &EndpointAttrib) -> Result<LedgerUpdate, ErrorCode> {
+        let value = Onboarding::endpoint_value(endpoint);
+
+        if Fetch::attrib(pool_handle, Some(did), did, "endpoint")? == Some(value.clone()) {
+            return Ok(LedgerUpdate::Unchanged);
+        }
+
+        let raw = json!({"endpoint": value}).to_string();
+        let request = Ledger::build_attrib_request(did, did, None, Some(&raw), None)?;
+        Onboarding::write(pool_handle, endpoint.wallet_handle, did, &request)?;
+
+        Ok(LedgerUpdate::Written)
+    }
+
+    fn write(pool_handle: IndyHandle, wallet_handle: IndyHandle, submitter_did: &str, request: &str) -> Result<(), ErrorCode> {
+        let response = Ledger::sign_and_submit_request(pool_handle, wallet_handle, submitter_did, request)?;
+        ReplyHandler::check(&response).map(|_| ())
+    }
+
+    fn nym_matches(nym: &NymData, verkey: &str, role: NymRole) -> Result<bool, ErrorCode> {
+        if nym.role.as_ref().map(String::as_str) != role.ledger_role() {
+            return Ok(false);
+        }
+
+        match nym.verkey {
+            Some(ref ledger_verkey) if ledger_verkey.starts_with('~') => Ok(*ledger_verkey == Did::abbreviate_verkey(&nym.dest, verkey)?),
+            Some(ref ledger_verkey) => Ok(ledger_verkey == verkey),
+            None => Ok(false)
+        }
+    }
+
+    fn endpoint_value(endpoint: &EndpointAttrib) -> Value {
+        match endpoint.transport_key {
+            Some(ref transport_key) => json!({"ha": endpoint.address, "verkey": transport_key}),
+            None => json!({"ha": endpoint.address})
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const VERKEY: &str = "GjZWsBLgZCR18aL468JAT7w9CZRiBnpxUPPgyQxh4voa";
+
+    fn nym(role: Option<&str>, verkey: Option<&str>) -> NymData {
+        NymData { dest: "VsKV7grR1BUE29mG2Fm2kX".to_string(), role: role.map(String::from), verkey: verkey.map(String::from), seq_no: None, txn_time: None }
+    }
+
+    #[test]
+    fn nym_matches_compares_role_and_verkey() {
+        assert!(Onboarding::nym_matches(&nym(Some("101"), Some(VERKEY)), VERKEY, NymRole::Endorser).unwrap());
+        assert!(Onboarding::nym_matches(&nym(None, Some(VERKEY)), VERKEY, NymRole::User).unwrap());
+
+        assert!(!Onboarding::nym_matches(&nym(Some("2"), Some(VERKEY)), VERKEY, NymRole::Endorser).unwrap());
+        assert!(!Onboarding::nym_matches(&nym(None, Some(VERKEY)), VERKEY, NymRole::Steward).unwrap());
+        assert!(!Onboarding::nym_matches(&nym(Some("101"), Some("other")), VERKEY, NymRole::Endorser).unwrap());
+        assert!(!Onboarding::nym_matches(&nym(Some("101"), None), VERKEY, NymRole::Endorser).unwrap());
+    }
+
+    #[test]
+    fn endpoint_value_works() {
+        let mut endpoint = EndpointAttrib { wallet_handle: 1, address: "127.0.0.1:9700".to_string(), transport_key: None };
+        assert_eq!(Onboarding::endpoint_value(&endpoint), json!({"ha": "127.0.0.1:9700"}));
+
+        endpoint.transport_key = Some(VERKEY.to_string());
+        assert_eq!(Onboarding::endpoint_value(&endpoint), json!({"ha": "127.0.0.1:9700", "verkey": VERKEY}));
+    }
+}
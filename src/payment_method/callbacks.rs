@@ -0,0 +1,242 @@
+use ErrorCode;
+
+use native::{CString, Error, Handle};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+
+use std::ffi::{self, CStr};
+use std::panic::{self, AssertUnwindSafe};
+
+use super::{PaymentMethod, PaymentOutput, Fees};
+
+/// Completion callback libindy passes to every payment method hook.
+type ResultCB = extern "C" fn(command_handle_: Handle, err: Error, result: CString) -> Error;
+
+fn opt_string_arg(arg: CString) -> Result<Option<String>, ErrorCode> {
+    if arg.is_null() {
+        return Ok(None);
+    }
+
+    unsafe { CStr::from_ptr(arg) }.to_str()
+        .map(|s| Some(s.to_string()))
+        .map_err(|_| ErrorCode::CommonInvalidStructure)
+}
+
+fn string_arg(arg: CString) -> Result<String, ErrorCode> {
+    opt_string_arg(arg)?.ok_or(ErrorCode::CommonInvalidStructure)
+}
+
+fn json_arg<T: DeserializeOwned>(arg: CString) -> Result<T, ErrorCode> {
+    serde_json::from_str(&string_arg(arg)?).map_err(|err| {
+        warn!("Invalid payment method argument - {}", err);
+        ErrorCode::CommonInvalidStructure
+    })
+}
+
+fn to_json<T: Serialize>(value: T) -> Result<String, ErrorCode> {
+    serde_json::to_string(&value).map_err(|_| ErrorCode::CommonInvalidState)
+}
+
+/// Runs the body of a hook and hands a successful result to libindy's callback. Errors are
+/// returned from the hook itself. Panics (e.g. of a `PaymentMethod` implementation) must not
+/// unwind into libindy, they are returned as `CommonInvalidState`.
+fn complete<F>(command_handle: Handle, cb: Option<ResultCB>, f: F) -> Error where F: FnOnce() -> Result<String, ErrorCode> {
+    let result = match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result,
+        Err(_) => {
+            error!("Payment method callback panicked");
+            Err(ErrorCode::CommonInvalidState)
+        }
+    };
+
+    let result = result.and_then(|result| ffi::CString::new(result).map_err(|_| ErrorCode::CommonInvalidState));
+
+    match (result, cb) {
+        (Ok(result), Some(cb)) => {
+            cb(command_handle, ErrorCode::Success as Error, result.as_ptr());
+            ErrorCode::Success as Error
+        }
+        (Ok(_), None) => ErrorCode::CommonInvalidState as Error,
+        (Err(err), _) => err as Error
+    }
+}
+
+pub extern "C" fn create_payment_address<T: PaymentMethod>(command_handle: Handle,
+                                                            wallet_handle: Handle,
+                                                            config: CString,
+                                                            cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let config = string_arg(config)?;
+
+        T::create_payment_address(wallet_handle, &config)
+    })
+}
+
+pub extern "C" fn add_request_fees<T: PaymentMethod>(command_handle: Handle,
+                                                      wallet_handle: Handle,
+                                                      submitter_did: CString,
+                                                      req_json: CString,
+                                                      inputs_json: CString,
+                                                      outputs_json: CString,
+                                                      extra: CString,
+                                                      cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let submitter_did = opt_string_arg(submitter_did)?;
+        let req_json = string_arg(req_json)?;
+        let inputs: Vec<String> = json_arg(inputs_json)?;
+        let outputs: Vec<PaymentOutput> = json_arg(outputs_json)?;
+        let extra = opt_string_arg(extra)?;
+
+        T::add_request_fees(wallet_handle, submitter_did.as_ref().map(String::as_str), &req_json, &inputs, &outputs, extra.as_ref().map(String::as_str))
+    })
+}
+
+pub extern "C" fn parse_response_with_fees<T: PaymentMethod>(command_handle: Handle,
+                                                              resp_json: CString,
+                                                              cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let resp_json = string_arg(resp_json)?;
+
+        T::parse_response_with_fees(&resp_json).and_then(to_json)
+    })
+}
+
+pub extern "C" fn build_get_payment_sources_request<T: PaymentMethod>(command_handle: Handle,
+                                                                       wallet_handle: Handle,
+                                                                       submitter_did: CString,
+                                                                       payment_address: CString,
+                                                                       cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let submitter_did = opt_string_arg(submitter_did)?;
+        let payment_address = string_arg(payment_address)?;
+
+        T::build_get_payment_sources_request(wallet_handle, submitter_did.as_ref().map(String::as_str), &payment_address)
+    })
+}
+
+pub extern "C" fn parse_get_payment_sources_response<T: PaymentMethod>(command_handle: Handle,
+                                                                        resp_json: CString,
+                                                                        cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let resp_json = string_arg(resp_json)?;
+
+        T::parse_get_payment_sources_response(&resp_json).and_then(to_json)
+    })
+}
+
+pub extern "C" fn build_payment_req<T: PaymentMethod>(command_handle: Handle,
+                                                       wallet_handle: Handle,
+                                                       submitter_did: CString,
+                                                       inputs_json: CString,
+                                                       outputs_json: CString,
+                                                       extra: CString,
+                                                       cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let submitter_did = opt_string_arg(submitter_did)?;
+        let inputs: Vec<String> = json_arg(inputs_json)?;
+        let outputs: Vec<PaymentOutput> = json_arg(outputs_json)?;
+        let extra = opt_string_arg(extra)?;
+
+        T::build_payment_req(wallet_handle, submitter_did.as_ref().map(String::as_str), &inputs, &outputs, extra.as_ref().map(String::as_str))
+    })
+}
+
+pub extern "C" fn parse_payment_response<T: PaymentMethod>(command_handle: Handle,
+                                                            resp_json: CString,
+                                                            cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let resp_json = string_arg(resp_json)?;
+
+        T::parse_payment_response(&resp_json).and_then(to_json)
+    })
+}
+
+pub extern "C" fn build_mint_req<T: PaymentMethod>(command_handle: Handle,
+                                                    wallet_handle: Handle,
+                                                    submitter_did: CString,
+                                                    outputs_json: CString,
+                                                    extra: CString,
+                                                    cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let submitter_did = opt_string_arg(submitter_did)?;
+        let outputs: Vec<PaymentOutput> = json_arg(outputs_json)?;
+        let extra = opt_string_arg(extra)?;
+
+        T::build_mint_req(wallet_handle, submitter_did.as_ref().map(String::as_str), &outputs, extra.as_ref().map(String::as_str))
+    })
+}
+
+pub extern "C" fn build_set_txn_fees_req<T: PaymentMethod>(command_handle: Handle,
+                                                            wallet_handle: Handle,
+                                                            submitter_did: CString,
+                                                            fees_json: CString,
+                                                            cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let submitter_did = opt_string_arg(submitter_did)?;
+        let fees: Fees = json_arg(fees_json)?;
+
+        T::build_set_txn_fees_req(wallet_handle, submitter_did.as_ref().map(String::as_str), &fees)
+    })
+}
+
+pub extern "C" fn build_get_txn_fees_req<T: PaymentMethod>(command_handle: Handle,
+                                                            wallet_handle: Handle,
+                                                            submitter_did: CString,
+                                                            cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let submitter_did = opt_string_arg(submitter_did)?;
+
+        T::build_get_txn_fees_req(wallet_handle, submitter_did.as_ref().map(String::as_str))
+    })
+}
+
+pub extern "C" fn parse_get_txn_fees_response<T: PaymentMethod>(command_handle: Handle,
+                                                                 resp_json: CString,
+                                                                 cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let resp_json = string_arg(resp_json)?;
+
+        T::parse_get_txn_fees_response(&resp_json).and_then(to_json)
+    })
+}
+
+pub extern "C" fn build_verify_payment_req<T: PaymentMethod>(command_handle: Handle,
+                                                              wallet_handle: Handle,
+                                                              submitter_did: CString,
+                                                              receipt: CString,
+                                                              cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let submitter_did = opt_string_arg(submitter_did)?;
+        let receipt = string_arg(receipt)?;
+
+        T::build_verify_payment_req(wallet_handle, submitter_did.as_ref().map(String::as_str), &receipt)
+    })
+}
+
+pub extern "C" fn parse_verify_payment_response<T: PaymentMethod>(command_handle: Handle,
+                                                                   resp_json: CString,
+                                                                   cb: Option<ResultCB>) -> Error {
+    complete(command_handle, cb, || {
+        let resp_json = string_arg(resp_json)?;
+
+        T::parse_verify_payment_response(&resp_json).and_then(to_json)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn complete_returns_error_of_hook() {
+        assert_eq!(complete(1, None, || Err(ErrorCode::PaymentSourceDoesNotExistError)), ErrorCode::PaymentSourceDoesNotExistError as Error);
+        assert_eq!(complete(1, None, || Ok("result".to_string())), ErrorCode::CommonInvalidState as Error);
+    }
+
+    #[test]
+    fn complete_catches_panic() {
+        assert_eq!(complete(1, None, || panic!("payment method failed")), ErrorCode::CommonInvalidState as Error);
+    }
+}
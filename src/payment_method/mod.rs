@@ -0,0 +1,128 @@
+pub(crate) mod callbacks;
+pub mod null;
+
+use {ErrorCode, IndyHandle};
+
+use std::collections::HashMap;
+
+/// Transaction fees: transaction type to amount.
+pub type Fees = HashMap<String, u64>;
+
+/// Output of a payment, mint or fees transaction.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentOutput {
+    #[serde(alias = "paymentAddress")]
+    pub recipient: String,
+    pub amount: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<String>,
+}
+
+/// Source (UTXO) owned by a payment address.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentSource {
+    pub source: String,
+    #[serde(rename = "paymentAddress")]
+    pub payment_address: String,
+    pub amount: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<String>,
+}
+
+/// Receipt of a processed payment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentReceipt {
+    pub receipt: String,
+    pub recipient: String,
+    pub amount: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<String>,
+}
+
+/// Payment transaction found by a verify request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentTxn {
+    pub sources: Vec<String>,
+    pub receipts: Vec<PaymentReceipt>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extra: Option<String>,
+}
+
+/// Payment method plugin, registered with `Payment::register_method_impl::<T>(payment_method)`.
+///
+/// libindy calls the plugin through extern callbacks; the crate converts their arguments,
+/// calls these functions and passes the results (serialized to json) to libindy's completion callback.
+/// The functions are called on libindy threads, so any state has to be kept in statics.
+///
+/// Requests and replies are ledger json that is specific to the payment method.
+pub trait PaymentMethod {
+    /// Creates a payment address and keeps its secret in the wallet.
+    fn create_payment_address(wallet_handle: IndyHandle, config: &str) -> Result<String, ErrorCode>;
+
+    /// Adds fees paid from `inputs` to `outputs` to the ledger request `req_json`.
+    fn add_request_fees(wallet_handle: IndyHandle,
+                        submitter_did: Option<&str>,
+                        req_json: &str,
+                        inputs: &[String],
+                        outputs: &[PaymentOutput],
+                        extra: Option<&str>) -> Result<String, ErrorCode>;
+
+    fn parse_response_with_fees(resp_json: &str) -> Result<Vec<PaymentReceipt>, ErrorCode>;
+
+    fn build_get_payment_sources_request(wallet_handle: IndyHandle,
+                                         submitter_did: Option<&str>,
+                                         payment_address: &str) -> Result<String, ErrorCode>;
+
+    fn parse_get_payment_sources_response(resp_json: &str) -> Result<Vec<PaymentSource>, ErrorCode>;
+
+    fn build_payment_req(wallet_handle: IndyHandle,
+                         submitter_did: Option<&str>,
+                         inputs: &[String],
+                         outputs: &[PaymentOutput],
+                         extra: Option<&str>) -> Result<String, ErrorCode>;
+
+    fn parse_payment_response(resp_json: &str) -> Result<Vec<PaymentReceipt>, ErrorCode>;
+
+    fn build_mint_req(wallet_handle: IndyHandle,
+                      submitter_did: Option<&str>,
+                      outputs: &[PaymentOutput],
+                      extra: Option<&str>) -> Result<String, ErrorCode>;
+
+    fn build_set_txn_fees_req(wallet_handle: IndyHandle,
+                              submitter_did: Option<&str>,
+                              fees: &Fees) -> Result<String, ErrorCode>;
+
+    fn build_get_txn_fees_req(wallet_handle: IndyHandle,
+                              submitter_did: Option<&str>) -> Result<String, ErrorCode>;
+
+    fn parse_get_txn_fees_response(resp_json: &str) -> Result<Fees, ErrorCode>;
+
+    fn build_verify_payment_req(wallet_handle: IndyHandle,
+                                submitter_did: Option<&str>,
+                                receipt: &str) -> Result<String, ErrorCode>;
+
+    fn parse_verify_payment_response(resp_json: &str) -> Result<PaymentTxn, ErrorCode>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use serde_json;
+
+    #[test]
+    fn payment_output_accepts_payment_address() {
+        let output: PaymentOutput = serde_json::from_str(r#"{"paymentAddress": "pay:null:1", "amount": 10}"#).unwrap();
+
+        assert_eq!(output, PaymentOutput { recipient: "pay:null:1".to_string(), amount: 10, extra: None });
+        assert_eq!(serde_json::to_string(&output).unwrap(), r#"{"recipient":"pay:null:1","amount":10}"#);
+    }
+
+    #[test]
+    fn payment_source_serializes_payment_address() {
+        let source = PaymentSource { source: "txo:null:1".to_string(), payment_address: "pay:null:1".to_string(), amount: 10, extra: Some("extra".to_string()) };
+
+        assert_eq!(serde_json::to_string(&source).unwrap(),
+                   r#"{"source":"txo:null:1","paymentAddress":"pay:null:1","amount":10,"extra":"extra"}"#);
+    }
+}
@@ -0,0 +1,410 @@
+use {ErrorCode, IndyHandle};
+use payments::Payment;
+use utils::sequence::SequenceUtils;
+
+use serde_json;
+use serde_json::Value;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+use super::{PaymentMethod, PaymentOutput, PaymentSource, PaymentReceipt, PaymentTxn, Fees};
+
+const MINT: &str = "10000";
+const PAYMENT: &str = "10001";
+const GET_SOURCES: &str = "10002";
+const SET_FEES: &str = "20000";
+const GET_FEES: &str = "20001";
+const GET_TXN: &str = "3";
+
+/// Result a null ledger gives to a request, taken by `reqId` when the response is parsed.
+enum Reply {
+    Receipts(Vec<PaymentReceipt>),
+    Sources(Vec<PaymentSource>),
+    Fees(Fees),
+    Txn(Result<PaymentTxn, ErrorCode>),
+}
+
+struct Source {
+    payment_address: String,
+    amount: u64,
+    extra: Option<String>,
+}
+
+#[derive(Default)]
+struct Ledger {
+    addresses: Vec<String>,
+    sources: HashMap<String, Source>,
+    txns: HashMap<String, PaymentTxn>,
+    fees: Fees,
+    replies: HashMap<i64, Reply>,
+}
+
+lazy_static! {
+    static ref LEDGER: Mutex<Ledger> = Default::default();
+}
+
+impl Ledger {
+    fn fee(&self, txn_type: &str) -> u64 {
+        self.fees.get(txn_type).cloned().unwrap_or(0)
+    }
+
+    /// Spends `inputs` and creates a source for every output. `inputs` must cover `outputs` and `fee` exactly.
+    fn transfer(&mut self, inputs: &[String], outputs: &[PaymentOutput], fee: u64, extra: Option<&str>) -> Result<Vec<PaymentReceipt>, ErrorCode> {
+        let mut spent = HashSet::new();
+        let mut total: u64 = 0;
+
+        for input in inputs {
+            if !spent.insert(input) {
+                warn!("Source {} is spent twice", input);
+                return Err(ErrorCode::CommonInvalidStructure);
+            }
+
+            let amount = self.sources.get(input).ok_or(ErrorCode::PaymentSourceDoesNotExistError)?.amount;
+            total = total.checked_add(amount).ok_or(ErrorCode::CommonInvalidStructure)?;
+        }
+
+        let required = outputs.iter()
+            .try_fold(fee, |required, output| required.checked_add(output.amount))
+            .ok_or(ErrorCode::PaymentInsufficientFundsError)?;
+
+        if total < required {
+            return Err(ErrorCode::PaymentInsufficientFundsError);
+        }
+        if total > required {
+            return Err(ErrorCode::PaymentExtraFundsError);
+        }
+
+        for input in inputs {
+            self.sources.remove(input);
+        }
+
+        let receipts = self.mint(outputs);
+
+        let txn = PaymentTxn { sources: inputs.to_vec(), receipts: receipts.clone(), extra: extra.map(String::from) };
+        for receipt in &receipts {
+            self.txns.insert(receipt.receipt.clone(), txn.clone());
+        }
+
+        Ok(receipts)
+    }
+
+    fn mint(&mut self, outputs: &[PaymentOutput]) -> Vec<PaymentReceipt> {
+        outputs.iter()
+            .map(|output| {
+                let source = format!("txo:null:{}", SequenceUtils::get_next_id());
+
+                self.sources.insert(source.clone(), Source {
+                    payment_address: output.recipient.clone(),
+                    amount: output.amount,
+                    extra: output.extra.clone(),
+                });
+
+                PaymentReceipt { receipt: source, recipient: output.recipient.clone(), amount: output.amount, extra: output.extra.clone() }
+            })
+            .collect()
+    }
+
+    fn sources(&self, payment_address: &str) -> Vec<PaymentSource> {
+        self.sources.iter()
+            .filter(|&(_, source)| source.payment_address == payment_address)
+            .map(|(id, source)| PaymentSource {
+                source: id.clone(),
+                payment_address: source.payment_address.clone(),
+                amount: source.amount,
+                extra: source.extra.clone(),
+            })
+            .collect()
+    }
+
+    /// Builds a request and records the reply the null ledger gives to it.
+    fn request(&mut self, submitter_did: Option<&str>, operation: Value, reply: Option<Reply>) -> String {
+        let req_id = i64::from(SequenceUtils::get_next_id());
+
+        if let Some(reply) = reply {
+            self.replies.insert(req_id, reply);
+        }
+
+        let mut request = json!({"reqId": req_id, "operation": operation});
+        if let Some(submitter_did) = submitter_did {
+            request["identifier"] = json!(submitter_did);
+        }
+
+        request.to_string()
+    }
+
+    /// Takes the reply for a response (or for the request itself) by its `reqId`, a reply
+    /// can be parsed once.
+    fn take_reply(&mut self, resp_json: &str) -> Result<Reply, ErrorCode> {
+        let response: Value = serde_json::from_str(resp_json).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        response["result"]["reqId"].as_i64()
+            .or_else(|| response["reqId"].as_i64())
+            .and_then(|req_id| self.replies.remove(&req_id))
+            .ok_or(ErrorCode::CommonInvalidStructure)
+    }
+}
+
+/// Reference payment method that keeps its "ledger" in process memory.
+///
+/// Payment addresses look like `pay:null:<id>` and sources (and receipts) like `txo:null:<id>`.
+/// Requests take effect on the in-memory ledger as soon as they are built, and every
+/// `Payment::parse_*` function accepts either the ledger reply or the request itself (once per
+/// request), so the whole `Payment` API can be exercised without a token ledger:
+///
+/// ```text
+/// let (mint_req, _) = Payment::build_mint_req(wallet_handle, None, &outputs_json, None)?;
+/// let (get_sources_req, _) = Payment::build_get_payment_sources_request(wallet_handle, None, &address)?;
+/// let sources_json = Payment::parse_get_payment_sources_response("null", &get_sources_req)?;
+/// ```
+///
+/// Payments and `Payment::add_request_fees` must spend exactly the amount of the outputs
+/// plus the fee set for the transaction type (`10001` for payments).
+pub struct NullPayment {}
+
+impl NullPayment {
+    /// Payment method name used by `NullPayment::register`.
+    pub const METHOD: &'static str = "null";
+
+    /// Registers the payment method with libindy as `NullPayment::METHOD`.
+    pub fn register() -> Result<(), ErrorCode> {
+        Payment::register_method_impl::<NullPayment>(NullPayment::METHOD)
+    }
+}
+
+impl PaymentMethod for NullPayment {
+    fn create_payment_address(_wallet_handle: IndyHandle, config: &str) -> Result<String, ErrorCode> {
+        let config: Value = serde_json::from_str(config).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        let address = match config["seed"].as_str() {
+            Some(seed) => format!("pay:null:{}", seed),
+            None => format!("pay:null:{}", SequenceUtils::get_next_id())
+        };
+
+        let mut ledger = LEDGER.lock().unwrap();
+        if !ledger.addresses.contains(&address) {
+            ledger.addresses.push(address.clone());
+        }
+
+        Ok(address)
+    }
+
+    fn add_request_fees(_wallet_handle: IndyHandle,
+                        _submitter_did: Option<&str>,
+                        req_json: &str,
+                        inputs: &[String],
+                        outputs: &[PaymentOutput],
+                        extra: Option<&str>) -> Result<String, ErrorCode> {
+        let mut request: Value = serde_json::from_str(req_json).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+        let req_id = request["reqId"].as_i64().ok_or(ErrorCode::CommonInvalidStructure)?;
+        let txn_type = request["operation"]["type"].as_str().unwrap_or_default().to_string();
+
+        let mut ledger = LEDGER.lock().unwrap();
+        let fee = ledger.fee(&txn_type);
+        let receipts = ledger.transfer(inputs, outputs, fee, extra)?;
+        ledger.replies.insert(req_id, Reply::Receipts(receipts));
+
+        request["fees"] = json!([inputs, outputs]);
+
+        Ok(request.to_string())
+    }
+
+    fn parse_response_with_fees(resp_json: &str) -> Result<Vec<PaymentReceipt>, ErrorCode> {
+        match LEDGER.lock().unwrap().take_reply(resp_json)? {
+            Reply::Receipts(receipts) => Ok(receipts),
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+
+    fn build_get_payment_sources_request(_wallet_handle: IndyHandle,
+                                         submitter_did: Option<&str>,
+                                         payment_address: &str) -> Result<String, ErrorCode> {
+        let mut ledger = LEDGER.lock().unwrap();
+        let sources = ledger.sources(payment_address);
+
+        Ok(ledger.request(submitter_did, json!({"type": GET_SOURCES, "address": payment_address}), Some(Reply::Sources(sources))))
+    }
+
+    fn parse_get_payment_sources_response(resp_json: &str) -> Result<Vec<PaymentSource>, ErrorCode> {
+        match LEDGER.lock().unwrap().take_reply(resp_json)? {
+            Reply::Sources(sources) => Ok(sources),
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+
+    fn build_payment_req(_wallet_handle: IndyHandle,
+                         submitter_did: Option<&str>,
+                         inputs: &[String],
+                         outputs: &[PaymentOutput],
+                         extra: Option<&str>) -> Result<String, ErrorCode> {
+        let mut ledger = LEDGER.lock().unwrap();
+        let fee = ledger.fee(PAYMENT);
+        let receipts = ledger.transfer(inputs, outputs, fee, extra)?;
+
+        Ok(ledger.request(submitter_did, json!({"type": PAYMENT, "inputs": inputs, "outputs": outputs, "extra": extra}), Some(Reply::Receipts(receipts))))
+    }
+
+    fn parse_payment_response(resp_json: &str) -> Result<Vec<PaymentReceipt>, ErrorCode> {
+        match LEDGER.lock().unwrap().take_reply(resp_json)? {
+            Reply::Receipts(receipts) => Ok(receipts),
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+
+    fn build_mint_req(_wallet_handle: IndyHandle,
+                      submitter_did: Option<&str>,
+                      outputs: &[PaymentOutput],
+                      extra: Option<&str>) -> Result<String, ErrorCode> {
+        let mut ledger = LEDGER.lock().unwrap();
+        let receipts = ledger.mint(outputs);
+
+        Ok(ledger.request(submitter_did, json!({"type": MINT, "outputs": outputs, "extra": extra}), Some(Reply::Receipts(receipts))))
+    }
+
+    fn build_set_txn_fees_req(_wallet_handle: IndyHandle,
+                              submitter_did: Option<&str>,
+                              fees: &Fees) -> Result<String, ErrorCode> {
+        let mut ledger = LEDGER.lock().unwrap();
+        ledger.fees.extend(fees.clone());
+
+        Ok(ledger.request(submitter_did, json!({"type": SET_FEES, "fees": fees}), None))
+    }
+
+    fn build_get_txn_fees_req(_wallet_handle: IndyHandle,
+                              submitter_did: Option<&str>) -> Result<String, ErrorCode> {
+        let mut ledger = LEDGER.lock().unwrap();
+        let fees = ledger.fees.clone();
+
+        Ok(ledger.request(submitter_did, json!({"type": GET_FEES}), Some(Reply::Fees(fees))))
+    }
+
+    fn parse_get_txn_fees_response(resp_json: &str) -> Result<Fees, ErrorCode> {
+        match LEDGER.lock().unwrap().take_reply(resp_json)? {
+            Reply::Fees(fees) => Ok(fees),
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+
+    fn build_verify_payment_req(_wallet_handle: IndyHandle,
+                                submitter_did: Option<&str>,
+                                receipt: &str) -> Result<String, ErrorCode> {
+        let mut ledger = LEDGER.lock().unwrap();
+        let txn = ledger.txns.get(receipt).cloned().ok_or(ErrorCode::PaymentSourceDoesNotExistError);
+
+        Ok(ledger.request(submitter_did, json!({"type": GET_TXN, "receipt": receipt}), Some(Reply::Txn(txn))))
+    }
+
+    fn parse_verify_payment_response(resp_json: &str) -> Result<PaymentTxn, ErrorCode> {
+        match LEDGER.lock().unwrap().take_reply(resp_json)? {
+            Reply::Txn(txn) => txn,
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(recipient: &str, amount: u64) -> PaymentOutput {
+        PaymentOutput { recipient: recipient.to_string(), amount, extra: None }
+    }
+
+    fn sources(address: &str) -> Vec<PaymentSource> {
+        let request = NullPayment::build_get_payment_sources_request(1, None, address).unwrap();
+        NullPayment::parse_get_payment_sources_response(&request).unwrap()
+    }
+
+    #[test]
+    fn mint_and_pay_works() {
+        let from = NullPayment::create_payment_address(1, "{}").unwrap();
+        let to = NullPayment::create_payment_address(1, "{}").unwrap();
+
+        let mint = NullPayment::build_mint_req(1, None, &[output(&from, 10)], None).unwrap();
+        let receipts = NullPayment::parse_payment_response(&mint).unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(sources(&from)[0].amount, 10);
+
+        let inputs = vec![receipts[0].receipt.clone()];
+        let payment = NullPayment::build_payment_req(1, None, &inputs, &[output(&to, 7), output(&from, 3)], Some("extra")).unwrap();
+        let receipts = NullPayment::parse_payment_response(&payment).unwrap();
+        assert_eq!(receipts.len(), 2);
+
+        assert_eq!(sources(&to)[0].amount, 7);
+        assert_eq!(sources(&from)[0].amount, 3);
+
+        let verify = NullPayment::build_verify_payment_req(1, None, &receipts[0].receipt).unwrap();
+        let txn = NullPayment::parse_verify_payment_response(&verify).unwrap();
+        assert_eq!(txn.sources, inputs);
+        assert_eq!(txn.extra, Some("extra".to_string()));
+    }
+
+    #[test]
+    fn payment_checks_amounts() {
+        let address = NullPayment::create_payment_address(1, "{}").unwrap();
+        let mint = NullPayment::build_mint_req(1, None, &[output(&address, 10)], None).unwrap();
+        let inputs = vec![NullPayment::parse_payment_response(&mint).unwrap()[0].receipt.clone()];
+
+        assert_eq!(NullPayment::build_payment_req(1, None, &inputs, &[output(&address, 11)], None).unwrap_err(), ErrorCode::PaymentInsufficientFundsError);
+        assert_eq!(NullPayment::build_payment_req(1, None, &inputs, &[output(&address, 9)], None).unwrap_err(), ErrorCode::PaymentExtraFundsError);
+        assert_eq!(NullPayment::build_payment_req(1, None, &["txo:null:unknown".to_string()], &[], None).unwrap_err(), ErrorCode::PaymentSourceDoesNotExistError);
+    }
+
+    #[test]
+    fn payment_rejects_duplicate_inputs() {
+        let address = NullPayment::create_payment_address(1, "{}").unwrap();
+        let mint = NullPayment::build_mint_req(1, None, &[output(&address, 10)], None).unwrap();
+        let input = NullPayment::parse_payment_response(&mint).unwrap()[0].receipt.clone();
+
+        let inputs = vec![input.clone(), input];
+
+        assert_eq!(NullPayment::build_payment_req(1, None, &inputs, &[output(&address, 20)], None).unwrap_err(), ErrorCode::CommonInvalidStructure);
+        assert_eq!(sources(&address)[0].amount, 10);
+    }
+
+    #[test]
+    fn payment_fails_for_overflowing_outputs() {
+        let address = NullPayment::create_payment_address(1, "{}").unwrap();
+        let mint = NullPayment::build_mint_req(1, None, &[output(&address, 10)], None).unwrap();
+        let inputs = vec![NullPayment::parse_payment_response(&mint).unwrap()[0].receipt.clone()];
+
+        let outputs = [output(&address, u64::max_value()), output(&address, 11)];
+
+        assert_eq!(NullPayment::build_payment_req(1, None, &inputs, &outputs, None).unwrap_err(), ErrorCode::PaymentInsufficientFundsError);
+    }
+
+    #[test]
+    fn create_payment_address_with_seed_works() {
+        let address = NullPayment::create_payment_address(1, r#"{"seed": "seed_for_null_address"}"#).unwrap();
+
+        assert_eq!(address, "pay:null:seed_for_null_address");
+    }
+
+    #[test]
+    fn parse_takes_reply_once() {
+        let address = NullPayment::create_payment_address(1, "{}").unwrap();
+        let request = NullPayment::build_get_payment_sources_request(1, None, &address).unwrap();
+
+        NullPayment::parse_get_payment_sources_response(&request).unwrap();
+
+        assert_eq!(NullPayment::parse_get_payment_sources_response(&request).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn replies_are_not_truncated_to_i32() {
+        let address = NullPayment::create_payment_address(1, "{}").unwrap();
+        let get_sources = NullPayment::build_get_payment_sources_request(1, None, &address).unwrap();
+        let req_id = serde_json::from_str::<Value>(&get_sources).unwrap()["reqId"].as_i64().unwrap();
+
+        let request = json!({"reqId": req_id + (1 << 32), "operation": {"type": "999999"}}).to_string();
+        let request = NullPayment::add_request_fees(1, None, &request, &[], &[], None).unwrap();
+
+        assert!(NullPayment::parse_response_with_fees(&request).unwrap().is_empty());
+        assert!(NullPayment::parse_get_payment_sources_response(&get_sources).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_unknown_response_fails() {
+        assert_eq!(NullPayment::parse_payment_response(r#"{"reqId": -1}"#).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
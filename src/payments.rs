@@ -9,6 +9,7 @@ use native::{ResponseEmptyCB,
           ResponseStringCB,
           ResponseStringStringCB};
 
+use payment_method::{PaymentMethod, callbacks};
 use utils::callbacks::ClosureHandler;
 use utils::results::ResultHandler;
 
@@ -128,6 +129,73 @@ impl Payment {
                                   cb)
     }
 
+    /// Registers a payment method implemented in Rust.
+    ///
+    /// The crate generates the callbacks libindy expects and passes the results
+    /// of `T` to libindy.
+    ///
+    /// # Arguments
+    /// * `payment_method` - payment method name (for example, 'sov')
+    pub fn register_method_impl<T: PaymentMethod>(payment_method: &str) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Payment::_register_method_impl::<T>(command_handle, payment_method, cb);
+
+        ResultHandler::empty(err, receiver)
+    }
+
+    /// Registers a payment method implemented in Rust.
+    ///
+    /// The crate generates the callbacks libindy expects and passes the results
+    /// of `T` to libindy.
+    ///
+    /// # Arguments
+    /// * `payment_method` - payment method name (for example, 'sov')
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn register_method_impl_timeout<T: PaymentMethod>(payment_method: &str, timeout: Duration) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Payment::_register_method_impl::<T>(command_handle, payment_method, cb);
+
+        ResultHandler::empty_timeout(err, receiver, timeout)
+    }
+
+    /// Registers a payment method implemented in Rust.
+    ///
+    /// The crate generates the callbacks libindy expects and passes the results
+    /// of `T` to libindy.
+    ///
+    /// # Arguments
+    /// * `payment_method` - payment method name (for example, 'sov')
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn register_method_impl_async<T: PaymentMethod, F: 'static>(payment_method: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec(Box::new(closure));
+
+        Payment::_register_method_impl::<T>(command_handle, payment_method, cb)
+    }
+
+    fn _register_method_impl<T: PaymentMethod>(command_handle: IndyHandle, payment_method: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+        Payment::_register_method(command_handle,
+                                  payment_method,
+                                  Some(callbacks::create_payment_address::<T>),
+                                  Some(callbacks::add_request_fees::<T>),
+                                  Some(callbacks::parse_response_with_fees::<T>),
+                                  Some(callbacks::build_get_payment_sources_request::<T>),
+                                  Some(callbacks::parse_get_payment_sources_response::<T>),
+                                  Some(callbacks::build_payment_req::<T>),
+                                  Some(callbacks::parse_payment_response::<T>),
+                                  Some(callbacks::build_mint_req::<T>),
+                                  Some(callbacks::build_set_txn_fees_req::<T>),
+                                  Some(callbacks::build_get_txn_fees_req::<T>),
+                                  Some(callbacks::parse_get_txn_fees_response::<T>),
+                                  Some(callbacks::build_verify_payment_req::<T>),
+                                  Some(callbacks::parse_verify_payment_response::<T>),
+                                  cb)
+    }
+
     fn _register_method(command_handle: IndyHandle,
                         payment_method: &str,
                         create_payment_address: Option<payments::CreatePaymentAddressCB>,
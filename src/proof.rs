@@ -0,0 +1,349 @@
+use ErrorCode;
+
+use serde_json;
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+use auto_prover::NonRevokedInterval;
+
+/// Proof request, see Prover::create_proof.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofRequest {
+    pub name: String,
+    pub version: String,
+    pub nonce: String,
+    #[serde(default)]
+    pub requested_attributes: HashMap<String, AttributeInfo>,
+    #[serde(default)]
+    pub requested_predicates: HashMap<String, PredicateInfo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub non_revoked: Option<NonRevokedInterval>,
+}
+
+/// Requested attribute, either a single `name` or a group of `names` from one credential.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub names: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restrictions: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub non_revoked: Option<NonRevokedInterval>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PredicateInfo {
+    pub name: String,
+    pub p_type: String,
+    pub p_value: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restrictions: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub non_revoked: Option<NonRevokedInterval>,
+}
+
+/// Proof created by Prover::create_proof.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Proof {
+    /// The cryptographic part of the proof, only used by libindy.
+    pub proof: Value,
+    pub requested_proof: RequestedProof,
+    /// Sub proofs, `sub_proof_index` of the requested proof points into this list.
+    pub identifiers: Vec<Identifier>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct RequestedProof {
+    #[serde(default)]
+    pub revealed_attrs: HashMap<String, RevealedAttributeInfo>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub revealed_attr_groups: HashMap<String, RevealedAttributeGroupInfo>,
+    #[serde(default)]
+    pub self_attested_attrs: HashMap<String, String>,
+    #[serde(default)]
+    pub unrevealed_attrs: HashMap<String, SubProofReferent>,
+    #[serde(default)]
+    pub predicates: HashMap<String, SubProofReferent>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevealedAttributeInfo {
+    pub sub_proof_index: usize,
+    pub raw: String,
+    pub encoded: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevealedAttributeGroupInfo {
+    pub sub_proof_index: usize,
+    pub values: HashMap<String, AttributeValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeValue {
+    pub raw: String,
+    pub encoded: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubProofReferent {
+    pub sub_proof_index: usize,
+}
+
+/// Ledger artifacts a sub proof of a proof is based on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Identifier {
+    pub schema_id: String,
+    pub cred_def_id: String,
+    pub rev_reg_id: Option<String>,
+    pub timestamp: Option<u64>,
+}
+
+/// Attribute revealed in a proof, with the credential definition it was issued for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevealedAttribute {
+    pub raw: String,
+    pub encoded: String,
+    pub schema_id: String,
+    pub cred_def_id: String,
+}
+
+/// Group of attributes revealed in a proof, with the credential definition they were issued for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevealedAttributeGroup {
+    /// Values by attribute name.
+    pub values: HashMap<String, AttributeValue>,
+    pub schema_id: String,
+    pub cred_def_id: String,
+}
+
+/// Result of Verifier::verify_proof_with_ledger.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifiedProof {
+    pub valid: bool,
+    /// Revealed attributes by the referent of the proof request.
+    pub revealed_attrs: HashMap<String, RevealedAttribute>,
+    /// Revealed attribute groups (requested with `names`) by the referent of the proof request.
+    pub revealed_attr_groups: HashMap<String, RevealedAttributeGroup>,
+}
+
+/// Answer of a proof to a requested attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedAttribute {
+    /// The requested attribute.
+    pub request: AttributeInfo,
+    /// Raw values by attribute name, empty for an unrevealed attribute.
+    pub values: HashMap<String, String>,
+    /// Sub proof of the attribute, `None` for a self attested attribute.
+    pub identifier: Option<Identifier>,
+}
+
+/// Answer of a proof to a requested predicate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPredicate {
+    pub request: PredicateInfo,
+    pub identifier: Identifier,
+}
+
+/// Proof with every referent mapped to the proof request entry it answers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedProof {
+    pub attributes: HashMap<String, ResolvedAttribute>,
+    pub predicates: HashMap<String, ResolvedPredicate>,
+}
+
+impl ProofRequest {
+    pub fn from_json(proof_request_json: &str) -> Result<ProofRequest, ErrorCode> {
+        serde_json::from_str(proof_request_json).map_err(|_| ErrorCode::CommonInvalidStructure)
+    }
+}
+
+impl Proof {
+    pub fn from_json(proof_json: &str) -> Result<Proof, ErrorCode> {
+        serde_json::from_str(proof_json).map_err(|_| ErrorCode::CommonInvalidStructure)
+    }
+
+    /// Returns the identifiers of a sub proof.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the proof has no such sub proof.
+    pub fn identifier(&self, sub_proof_index: usize) -> Result<&Identifier, ErrorCode> {
+        self.identifiers.get(sub_proof_index).ok_or(ErrorCode::CommonInvalidStructure)
+    }
+
+    /// Maps each referent of the proof to the entry of the proof request it answers.
+    ///
+    /// The proof itself is not verified (see Verifier::verify_proof).
+    ///
+    /// # Arguments
+    /// * `proof_request` - the proof request the proof was created for.
+    ///
+    /// # Returns
+    /// `CommonInvalidStructure` if the request has no entry for a referent of the proof or a
+    /// referent points to a missing sub proof.
+    pub fn resolve(&self, proof_request: &ProofRequest) -> Result<ResolvedProof, ErrorCode> {
+        let requested_proof = &self.requested_proof;
+        let mut attributes = HashMap::new();
+
+        for (referent, attr) in &requested_proof.revealed_attrs {
+            let request = Proof::requested_attribute(proof_request, referent)?;
+            let name = request.name.clone().ok_or(ErrorCode::CommonInvalidStructure)?;
+
+            attributes.insert(referent.clone(), ResolvedAttribute {
+                request,
+                values: vec![(name, attr.raw.clone())].into_iter().collect(),
+                identifier: Some(self.identifier(attr.sub_proof_index)?.clone()),
+            });
+        }
+
+        for (referent, group) in &requested_proof.revealed_attr_groups {
+            attributes.insert(referent.clone(), ResolvedAttribute {
+                request: Proof::requested_attribute(proof_request, referent)?,
+                values: group.values.iter().map(|(name, value)| (name.clone(), value.raw.clone())).collect(),
+                identifier: Some(self.identifier(group.sub_proof_index)?.clone()),
+            });
+        }
+
+        for (referent, raw) in &requested_proof.self_attested_attrs {
+            let request = Proof::requested_attribute(proof_request, referent)?;
+            let name = request.name.clone().ok_or(ErrorCode::CommonInvalidStructure)?;
+
+            attributes.insert(referent.clone(), ResolvedAttribute {
+                request,
+                values: vec![(name, raw.clone())].into_iter().collect(),
+                identifier: None,
+            });
+        }
+
+        for (referent, attr) in &requested_proof.unrevealed_attrs {
+            attributes.insert(referent.clone(), ResolvedAttribute {
+                request: Proof::requested_attribute(proof_request, referent)?,
+                values: HashMap::new(),
+                identifier: Some(self.identifier(attr.sub_proof_index)?.clone()),
+            });
+        }
+
+        let mut predicates = HashMap::new();
+
+        for (referent, predicate) in &requested_proof.predicates {
+            let request = match proof_request.requested_predicates.get(referent) {
+                Some(request) => request.clone(),
+                None => {
+                    warn!("Proof request has no predicate {}", referent);
+                    return Err(ErrorCode::CommonInvalidStructure);
+                }
+            };
+
+            predicates.insert(referent.clone(), ResolvedPredicate {
+                request,
+                identifier: self.identifier(predicate.sub_proof_index)?.clone(),
+            });
+        }
+
+        Ok(ResolvedProof { attributes, predicates })
+    }
+
+    fn requested_attribute(proof_request: &ProofRequest, referent: &str) -> Result<AttributeInfo, ErrorCode> {
+        match proof_request.requested_attributes.get(referent) {
+            Some(request) => Ok(request.clone()),
+            None => {
+                warn!("Proof request has no attribute {}", referent);
+                Err(ErrorCode::CommonInvalidStructure)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PROOF_REQUEST: &str = r#"{
+        "nonce": "123432421212",
+        "name": "proof_req_1",
+        "version": "0.1",
+        "requested_attributes": {
+            "attr1_referent": {"name": "name"},
+            "attr2_referent": {"name": "sex"},
+            "attr3_referent": {"name": "phone"},
+            "attr4_referent": {"names": ["name", "height"]}
+        },
+        "requested_predicates": {"predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}}
+    }"#;
+
+    const PROOF: &str = r#"{
+        "proof": {"proofs": [], "aggregated_proof": {}},
+        "requested_proof": {
+            "revealed_attrs": {"attr1_referent": {"sub_proof_index": 0, "raw": "Alex", "encoded": "1139481716457488690172217916278103335"}},
+            "revealed_attr_groups": {"attr4_referent": {"sub_proof_index": 0, "values": {"name": {"raw": "Alex", "encoded": "1139481716457488690172217916278103335"}, "height": {"raw": "175", "encoded": "175"}}}},
+            "self_attested_attrs": {"attr3_referent": "8-800-300"},
+            "unrevealed_attrs": {"attr2_referent": {"sub_proof_index": 0}},
+            "predicates": {"predicate1_referent": {"sub_proof_index": 0}}
+        },
+        "identifiers": [{"schema_id": "schema", "cred_def_id": "cred_def", "rev_reg_id": null, "timestamp": null}]
+    }"#;
+
+    fn identifier() -> Identifier {
+        Identifier { schema_id: "schema".to_string(), cred_def_id: "cred_def".to_string(), rev_reg_id: None, timestamp: None }
+    }
+
+    #[test]
+    fn proof_parse() {
+        let proof = Proof::from_json(PROOF).unwrap();
+
+        assert_eq!(proof.requested_proof.revealed_attrs["attr1_referent"].raw, "Alex");
+        assert_eq!(proof.requested_proof.revealed_attr_groups["attr4_referent"].values["height"].raw, "175");
+        assert_eq!(proof.requested_proof.self_attested_attrs["attr3_referent"], "8-800-300");
+        assert_eq!(proof.requested_proof.predicates["predicate1_referent"].sub_proof_index, 0);
+        assert_eq!(proof.identifiers, vec![identifier()]);
+    }
+
+    #[test]
+    fn proof_parse_works_without_attr_groups() {
+        let proof = r#"{"proof": {}, "requested_proof": {"revealed_attrs": {}, "self_attested_attrs": {}, "unrevealed_attrs": {}, "predicates": {}}, "identifiers": []}"#;
+
+        assert_eq!(Proof::from_json(proof).unwrap().requested_proof, RequestedProof::default());
+    }
+
+    #[test]
+    fn resolve_works() {
+        let proof = Proof::from_json(PROOF).unwrap();
+        let proof_request = ProofRequest::from_json(PROOF_REQUEST).unwrap();
+
+        let resolved = proof.resolve(&proof_request).unwrap();
+
+        let name = &resolved.attributes["attr1_referent"];
+        assert_eq!(name.request.name, Some("name".to_string()));
+        assert_eq!(name.values["name"], "Alex");
+        assert_eq!(name.identifier, Some(identifier()));
+
+        assert_eq!(resolved.attributes["attr4_referent"].values["height"], "175");
+        assert_eq!(resolved.attributes["attr3_referent"].values["phone"], "8-800-300");
+        assert_eq!(resolved.attributes["attr3_referent"].identifier, None);
+        assert!(resolved.attributes["attr2_referent"].values.is_empty());
+
+        let age = &resolved.predicates["predicate1_referent"];
+        assert_eq!(age.request.p_value, 18);
+        assert_eq!(age.identifier, identifier());
+    }
+
+    #[test]
+    fn resolve_fails_for_unknown_referent() {
+        let proof = Proof::from_json(PROOF).unwrap();
+        let mut proof_request = ProofRequest::from_json(PROOF_REQUEST).unwrap();
+        proof_request.requested_attributes.remove("attr1_referent");
+
+        assert_eq!(proof.resolve(&proof_request).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn resolve_fails_for_missing_sub_proof() {
+        let mut proof = Proof::from_json(PROOF).unwrap();
+        proof.identifiers.clear();
+
+        assert_eq!(proof.resolve(&ProofRequest::from_json(PROOF_REQUEST).unwrap()).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
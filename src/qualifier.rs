@@ -0,0 +1,231 @@
+use ErrorCode;
+
+const DID_PREFIX: &str = "did";
+const SCHEMA_PREFIX: &str = "schema";
+const CRED_DEF_PREFIX: &str = "creddef";
+
+const SCHEMA_MARKER: &str = "2";
+const CRED_DEF_MARKER: &str = "3";
+
+/// Converts DIDs, schema ids and credential definition ids between the unqualified
+/// (indy-sdk legacy) form and the fully qualified form.
+///
+/// # Examples
+/// * DID - `NcYxiDXkpYi6ov5FcYDi1e` <=> `did:sov:NcYxiDXkpYi6ov5FcYDi1e`
+/// * Schema id - `NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0` <=> `schema:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0`
+/// * Credential definition id - `NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag` <=> `creddef:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag`
+///
+/// Prover and Verifier accept artifacts in both forms. Proof requests that restrict
+/// on fully qualified identifiers must be created with `"ver": "2.0"`.
+pub struct Qualifier {}
+
+impl Qualifier {
+    /// Checks whether `entity` is a fully qualified DID, schema id or credential definition id.
+    pub fn is_fully_qualified(entity: &str) -> bool {
+        Qualifier::method(entity).is_some()
+    }
+
+    /// Returns the DID method of a fully qualified entity, `None` for unqualified entities.
+    pub fn method(entity: &str) -> Option<&str> {
+        let mut parts = entity.splitn(3, ':');
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(prefix), Some(method), Some(_)) if !method.is_empty() &&
+                (prefix == DID_PREFIX || prefix == SCHEMA_PREFIX || prefix == CRED_DEF_PREFIX) => Some(method),
+            _ => None
+        }
+    }
+
+    /// Builds a fully qualified DID `did:<method>:<did>`. A DID that is already qualified gets its method replaced.
+    pub fn qualify_did(did: &str, method: &str) -> String {
+        format!("{}:{}:{}", DID_PREFIX, method, Qualifier::unqualify_did(did))
+    }
+
+    /// Strips the `did:<method>:` prefix from a DID. Unqualified DIDs are returned as is.
+    pub fn unqualify_did(did: &str) -> String {
+        if did.starts_with("did:") {
+            did.splitn(3, ':').nth(2).unwrap_or(did).to_string()
+        } else {
+            did.to_string()
+        }
+    }
+
+    /// Builds a fully qualified schema id `schema:<method>:did:<method>:<did>:2:<name>:<version>`.
+    /// A schema referenced by its ledger sequence number is returned as is.
+    pub fn qualify_schema_id(schema_id: &str, method: &str) -> Result<String, ErrorCode> {
+        if Qualifier::is_seq_no(schema_id) {
+            return Ok(schema_id.to_string());
+        }
+
+        let (did, name, version) = Qualifier::parse_schema_id(schema_id)?;
+
+        Ok(format!("{}:{}:{}:{}:{}:{}", SCHEMA_PREFIX, method, Qualifier::qualify_did(&did, method), SCHEMA_MARKER, name, version))
+    }
+
+    /// Converts a schema id to the unqualified form `<did>:2:<name>:<version>`.
+    pub fn unqualify_schema_id(schema_id: &str) -> Result<String, ErrorCode> {
+        if Qualifier::is_seq_no(schema_id) {
+            return Ok(schema_id.to_string());
+        }
+
+        let (did, name, version) = Qualifier::parse_schema_id(schema_id)?;
+
+        Ok(format!("{}:{}:{}:{}", did, SCHEMA_MARKER, name, version))
+    }
+
+    /// Builds a fully qualified credential definition id
+    /// `creddef:<method>:did:<method>:<did>:3:<signature_type>:<schema_ref>:<tag>`.
+    /// A schema reference given as a schema id is qualified as well.
+    pub fn qualify_cred_def_id(cred_def_id: &str, method: &str) -> Result<String, ErrorCode> {
+        let (did, signature_type, schema_ref, tag) = Qualifier::parse_cred_def_id(cred_def_id)?;
+
+        let mut id = format!("{}:{}:{}:{}:{}:{}",
+                             CRED_DEF_PREFIX, method, Qualifier::qualify_did(&did, method), CRED_DEF_MARKER,
+                             signature_type, Qualifier::qualify_schema_id(&schema_ref, method)?);
+
+        if let Some(tag) = tag {
+            id.push(':');
+            id.push_str(&tag);
+        }
+
+        Ok(id)
+    }
+
+    /// Converts a credential definition id to the unqualified form `<did>:3:<signature_type>:<schema_ref>:<tag>`.
+    pub fn unqualify_cred_def_id(cred_def_id: &str) -> Result<String, ErrorCode> {
+        let (did, signature_type, schema_ref, tag) = Qualifier::parse_cred_def_id(cred_def_id)?;
+
+        let mut id = format!("{}:{}:{}:{}", did, CRED_DEF_MARKER, signature_type, Qualifier::unqualify_schema_id(&schema_ref)?);
+
+        if let Some(tag) = tag {
+            id.push(':');
+            id.push_str(&tag);
+        }
+
+        Ok(id)
+    }
+
+    fn is_seq_no(id: &str) -> bool {
+        !id.is_empty() && id.chars().all(|c| c.is_digit(10))
+    }
+
+    /// Removes the `<prefix>:<method>:` part of a qualified schema or credential definition id.
+    fn strip_prefix<'a>(id: &'a str, prefix: &str) -> &'a str {
+        let mut parts = id.splitn(3, ':');
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(p), Some(_), Some(rest)) if p == prefix => rest,
+            _ => id
+        }
+    }
+
+    fn parse_schema_id(schema_id: &str) -> Result<(String, String, String), ErrorCode> {
+        let unprefixed = Qualifier::strip_prefix(schema_id, SCHEMA_PREFIX);
+        let parts: Vec<&str> = unprefixed.split(':').collect();
+
+        // `did:<method>:<did>` takes three parts in the qualified form
+        let offset = if parts.len() == 6 && parts[0] == DID_PREFIX { 2 } else { 0 };
+
+        if parts.len() != 4 + offset || parts[1 + offset] != SCHEMA_MARKER {
+            warn!("Invalid schema id - {}", schema_id);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        Ok((parts[offset].to_string(), parts[2 + offset].to_string(), parts[3 + offset].to_string()))
+    }
+
+    fn parse_cred_def_id(cred_def_id: &str) -> Result<(String, String, String, Option<String>), ErrorCode> {
+        let unprefixed = Qualifier::strip_prefix(cred_def_id, CRED_DEF_PREFIX);
+        let parts: Vec<&str> = unprefixed.split(':').collect();
+
+        let offset = if parts.len() > 2 && parts[0] == DID_PREFIX { 2 } else { 0 };
+
+        if parts.len() < 4 + offset || parts[1 + offset] != CRED_DEF_MARKER {
+            warn!("Invalid credential definition id - {}", cred_def_id);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        let did = parts[offset].to_string();
+        let signature_type = parts[2 + offset].to_string();
+        let rest = &parts[3 + offset..];
+
+        // The schema reference is either a sequence number or a (possibly qualified) schema id,
+        // so only a trailing part that isn't part of a schema id can be the tag.
+        let (schema_ref, tag) = match rest.len() {
+            1 => (rest[0].to_string(), None),
+            _ if Qualifier::is_seq_no(rest[0]) && rest.len() == 2 => (rest[0].to_string(), Some(rest[1].to_string())),
+            _ if Qualifier::parse_schema_id(&rest.join(":")).is_ok() => (rest.join(":"), None),
+            n => (rest[..n - 1].join(":"), Some(rest[n - 1].to_string())),
+        };
+
+        Ok((did, signature_type, schema_ref, tag))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DID: &str = "NcYxiDXkpYi6ov5FcYDi1e";
+    const DID_QUALIFIED: &str = "did:sov:NcYxiDXkpYi6ov5FcYDi1e";
+    const SCHEMA_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+    const SCHEMA_ID_QUALIFIED: &str = "schema:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+    const CRED_DEF_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag";
+    const CRED_DEF_ID_QUALIFIED: &str = "creddef:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag";
+
+    #[test]
+    fn did_round_trip() {
+        assert_eq!(Qualifier::qualify_did(DID, "sov"), DID_QUALIFIED);
+        assert_eq!(Qualifier::unqualify_did(DID_QUALIFIED), DID);
+        assert_eq!(Qualifier::unqualify_did(DID), DID);
+    }
+
+    #[test]
+    fn qualify_did_replaces_method() {
+        assert_eq!(Qualifier::qualify_did(DID_QUALIFIED, "peer"), "did:peer:NcYxiDXkpYi6ov5FcYDi1e");
+    }
+
+    #[test]
+    fn method_and_is_fully_qualified() {
+        assert_eq!(Qualifier::method(DID_QUALIFIED), Some("sov"));
+        assert_eq!(Qualifier::method(SCHEMA_ID_QUALIFIED), Some("sov"));
+        assert_eq!(Qualifier::method(CRED_DEF_ID_QUALIFIED), Some("sov"));
+        assert!(!Qualifier::is_fully_qualified(DID));
+        assert!(!Qualifier::is_fully_qualified(SCHEMA_ID));
+        assert!(!Qualifier::is_fully_qualified(CRED_DEF_ID));
+    }
+
+    #[test]
+    fn schema_id_round_trip() {
+        assert_eq!(Qualifier::qualify_schema_id(SCHEMA_ID, "sov").unwrap(), SCHEMA_ID_QUALIFIED);
+        assert_eq!(Qualifier::unqualify_schema_id(SCHEMA_ID_QUALIFIED).unwrap(), SCHEMA_ID);
+        assert_eq!(Qualifier::unqualify_schema_id(SCHEMA_ID).unwrap(), SCHEMA_ID);
+    }
+
+    #[test]
+    fn schema_id_invalid() {
+        assert_eq!(Qualifier::qualify_schema_id("NcYxiDXkpYi6ov5FcYDi1e:3:gvt:1.0", "sov").unwrap_err(), ErrorCode::CommonInvalidStructure);
+        assert_eq!(Qualifier::unqualify_schema_id("gvt").unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn cred_def_id_round_trip() {
+        assert_eq!(Qualifier::qualify_cred_def_id(CRED_DEF_ID, "sov").unwrap(), CRED_DEF_ID_QUALIFIED);
+        assert_eq!(Qualifier::unqualify_cred_def_id(CRED_DEF_ID_QUALIFIED).unwrap(), CRED_DEF_ID);
+        assert_eq!(Qualifier::unqualify_cred_def_id(CRED_DEF_ID).unwrap(), CRED_DEF_ID);
+    }
+
+    #[test]
+    fn cred_def_id_without_tag() {
+        assert_eq!(Qualifier::qualify_cred_def_id("NcYxiDXkpYi6ov5FcYDi1e:3:CL:1", "sov").unwrap(), "creddef:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:3:CL:1");
+    }
+
+    #[test]
+    fn cred_def_id_with_schema_id_reference() {
+        let cred_def_id = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0:tag";
+        let qualified = "creddef:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:3:CL:schema:sov:did:sov:NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0:tag";
+
+        assert_eq!(Qualifier::qualify_cred_def_id(cred_def_id, "sov").unwrap(), qualified);
+        assert_eq!(Qualifier::unqualify_cred_def_id(qualified).unwrap(), cred_def_id);
+    }
+}
@@ -0,0 +1,400 @@
+use {ErrorCode, IndyHandle};
+
+use std::collections::HashMap;
+
+use anoncreds::Issuer;
+use blob_storage::Blob;
+use ledger::Ledger;
+use utils::records::{Record, Records};
+use utils::reply::ReplyHandler;
+
+/// Type of the revocation registries created by `RevocationRegistryManager`.
+pub const REVOC_DEF_TYPE: &'static str = "CL_ACCUM";
+
+const REGISTRY_RECORD: &'static str = "IndyWrapper::RevocationRegistry";
+const CREDENTIAL_RECORD: &'static str = "IndyWrapper::RevocationCredential";
+
+pub(crate) const TAILS_TYPE: &'static str = "default";
+
+/// How `RevocationRegistryManager` creates new revocation registries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RevocationRegistryConfig {
+    /// Directory the tails files are written to and read from.
+    pub tails_dir: String,
+    /// Maximum number of credentials in one registry.
+    pub max_cred_num: u32,
+    /// `ISSUANCE_BY_DEFAULT` or `ISSUANCE_ON_DEMAND`, libindy's default if `None`.
+    pub issuance_type: Option<String>,
+    /// Registry tags are the prefix followed by a sequence number.
+    pub tag_prefix: String,
+}
+
+impl RevocationRegistryConfig {
+    pub fn new(tails_dir: &str, max_cred_num: u32) -> RevocationRegistryConfig {
+        RevocationRegistryConfig {
+            tails_dir: tails_dir.to_string(),
+            max_cred_num,
+            issuance_type: None,
+            tag_prefix: "r".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RevocationRegistryState {
+    /// Created in the wallet, but not published yet.
+    Created,
+    /// Published, new credentials are issued into it.
+    Active,
+    /// Full or replaced by a newer registry, no credentials are issued into it.
+    Full,
+}
+
+impl RevocationRegistryState {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            RevocationRegistryState::Created => "created",
+            RevocationRegistryState::Active => "active",
+            RevocationRegistryState::Full => "full",
+        }
+    }
+}
+
+/// Revocation registry managed by `RevocationRegistryManager`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevocationRegistry {
+    pub rev_reg_id: String,
+    pub cred_def_id: String,
+    pub tag: String,
+    pub tails_dir: String,
+    pub state: RevocationRegistryState,
+}
+
+impl RevocationRegistry {
+    /// Opens a reader of the tails file of the registry.
+    pub fn open_tails_reader(&self) -> Result<IndyHandle, ErrorCode> {
+        Blob::open_reader(TAILS_TYPE, &RevocationRegistry::tails_config(&self.tails_dir))
+    }
+
+    pub(crate) fn tails_config(tails_dir: &str) -> String {
+        json!({"base_dir": tails_dir, "uri_pattern": ""}).to_string()
+    }
+}
+
+/// Registry and credential revocation ID of an issued credential.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CredentialLocation {
+    pub rev_reg_id: String,
+    pub cred_rev_id: String,
+}
+
+/// Credential issued by `RevocationRegistryManager::issue_credential`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssuedCredential {
+    /// Id the location of the credential is stored with.
+    pub cred_id: String,
+    pub cred_json: String,
+    pub location: CredentialLocation,
+    /// Revocation registry delta of an `ISSUANCE_ON_DEMAND` registry that could not be
+    /// published, see `RevocationRegistryManager::publish_delta`.
+    pub unpublished_delta: Option<String>,
+}
+
+/// Result of `RevocationRegistryManager::revoke_credentials`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RevocationReport {
+    /// Revoked credentials whose registry entry was published.
+    pub revoked: Vec<CredentialLocation>,
+    /// Credentials that were not revoked or whose registry entry was not published.
+    pub failed: Vec<(CredentialLocation, ErrorCode)>,
+    /// Published (merged) revocation registry deltas per registry id.
+    pub published: HashMap<String, Vec<String>>,
+    /// Merged revocation registry deltas per registry id that are revoked in the wallet but could
+    /// not be published, see `RevocationRegistryManager::publish_delta`.
+    pub unpublished: HashMap<String, Vec<String>>,
+}
+
+impl RevocationReport {
+    fn fail(&mut self, credentials: Vec<&CredentialLocation>, err: ErrorCode) {
+        self.failed.extend(credentials.into_iter().map(|credential| (credential.clone(), err)));
+    }
+}
+
+/// Owns the revocation registries of one credential definition.
+///
+/// Credentials are issued into the active registry. When it is full, the next registry is
+/// created and published on the ledger. Registries and the registry of every issued credential
+/// are kept in non-secret wallet records, so a manager created later for the same wallet and
+/// credential definition continues where the previous one stopped.
+pub struct RevocationRegistryManager {
+    pool_handle: IndyHandle,
+    wallet_handle: IndyHandle,
+    issuer_did: String,
+    cred_def_id: String,
+    config: RevocationRegistryConfig,
+}
+
+impl RevocationRegistryManager {
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `issuer_did` - DID of the issuer, it signs the registry transactions.
+    /// * `cred_def_id` - id of a credential definition with revocation support stored in the wallet.
+    /// * `config` - how new registries are created.
+    pub fn new(pool_handle: IndyHandle, wallet_handle: IndyHandle, issuer_did: &str, cred_def_id: &str, config: RevocationRegistryConfig) -> RevocationRegistryManager {
+        RevocationRegistryManager {
+            pool_handle,
+            wallet_handle,
+            issuer_did: issuer_did.to_string(),
+            cred_def_id: cred_def_id.to_string(),
+            config,
+        }
+    }
+
+    /// Issues a credential into the active registry.
+    ///
+    /// Creates and publishes a registry if there is no active one or the active one is full.
+    /// Deltas of `ISSUANCE_ON_DEMAND` registries are published on the ledger. If that fails, the
+    /// credential is still issued and its location stored; the delta is returned in
+    /// `IssuedCredential::unpublished_delta` to publish later with `RevocationRegistryManager::publish_delta`.
+    ///
+    /// # Arguments
+    /// * `cred_offer_json` - a cred offer created by Issuer::create_credential_offer.
+    /// * `cred_req_json` - a credential request created by Prover::create_credential_req.
+    /// * `cred_values_json` - a credential containing attribute values for each of requested attribute names.
+    /// * `cred_id` - id to store the location of the credential with, `<rev_reg_id>:<cred_rev_id>` if `None`.
+    pub fn issue_credential(&self, cred_offer_json: &str, cred_req_json: &str, cred_values_json: &str, cred_id: Option<&str>) -> Result<IssuedCredential, ErrorCode> {
+        let registry = match self.active_registry()? {
+            Some(registry) => registry,
+            None => self.create_registry()?
+        };
+
+        let (cred_json, location, rev_reg_delta_json) = match self.issue_into(&registry, cred_offer_json, cred_req_json, cred_values_json) {
+            Err(ErrorCode::AnoncredsRevocationRegistryFullError) => {
+                info!("Revocation registry {} is full", registry.rev_reg_id);
+                self.set_state(&registry.rev_reg_id, RevocationRegistryState::Full)?;
+
+                let registry = self.create_registry()?;
+                self.issue_into(&registry, cred_offer_json, cred_req_json, cred_values_json)?
+            }
+            result => result?
+        };
+
+        let cred_id = cred_id.map(String::from).unwrap_or_else(|| format!("{}:{}", location.rev_reg_id, location.cred_rev_id));
+
+        let mut tags = HashMap::new();
+        tags.insert("cred_def_id".to_string(), self.cred_def_id.clone());
+        tags.insert("rev_reg_id".to_string(), location.rev_reg_id.clone());
+        tags.insert("cred_rev_id".to_string(), location.cred_rev_id.clone());
+        Records::add(self.wallet_handle, CREDENTIAL_RECORD, &cred_id, &location, &tags)?;
+
+        let unpublished_delta = rev_reg_delta_json.and_then(|rev_reg_delta_json| {
+            match self.publish_delta(&location.rev_reg_id, &rev_reg_delta_json) {
+                Ok(()) => None,
+                Err(_) => {
+                    warn!("Failed to publish revocation registry entry for {}", location.rev_reg_id);
+                    Some(rev_reg_delta_json)
+                }
+            }
+        });
+
+        Ok(IssuedCredential { cred_id, cred_json, location, unpublished_delta })
+    }
+
+    /// Creates the next registry, publishes its definition and initial entry and makes it the active one.
+    pub fn create_registry(&self) -> Result<RevocationRegistry, ErrorCode> {
+        let tag = format!("{}{}", self.config.tag_prefix, self.registries()?.len() + 1);
+
+        let mut config = json!({"max_cred_num": self.config.max_cred_num});
+        if let Some(ref issuance_type) = self.config.issuance_type {
+            config["issuance_type"] = json!(issuance_type);
+        }
+
+        let tails_writer_handle = Blob::open_writer(TAILS_TYPE, &RevocationRegistry::tails_config(&self.config.tails_dir))?;
+        let (rev_reg_id, rev_reg_def_json, rev_reg_entry_json) =
+            Issuer::create_and_store_revoc_reg(self.wallet_handle, &self.issuer_did, Some(REVOC_DEF_TYPE), &tag, &self.cred_def_id, &config.to_string(), tails_writer_handle)?;
+
+        // Recorded before publishing so that a failed publication does not reuse the tag.
+        let mut registry = RevocationRegistry {
+            rev_reg_id,
+            cred_def_id: self.cred_def_id.clone(),
+            tag,
+            tails_dir: self.config.tails_dir.clone(),
+            state: RevocationRegistryState::Created,
+        };
+        Records::add(self.wallet_handle, REGISTRY_RECORD, &registry.rev_reg_id, &registry, &self.registry_tags(registry.state))?;
+
+        let request = Ledger::build_revoc_reg_def_request(&self.issuer_did, &rev_reg_def_json)?;
+        self.publish(&request)?;
+
+        let request = Ledger::build_revoc_reg_entry_request(&self.issuer_did, &registry.rev_reg_id, REVOC_DEF_TYPE, &rev_reg_entry_json)?;
+        self.publish(&request)?;
+
+        if let Some(active) = self.active_registry()? {
+            self.set_state(&active.rev_reg_id, RevocationRegistryState::Full)?;
+        }
+
+        self.set_state(&registry.rev_reg_id, RevocationRegistryState::Active)?;
+        registry.state = RevocationRegistryState::Active;
+
+        Ok(registry)
+    }
+
+    /// Returns the registry new credentials are issued into.
+    pub fn active_registry(&self) -> Result<Option<RevocationRegistry>, ErrorCode> {
+        let query = json!({"cred_def_id": self.cred_def_id, "state": RevocationRegistryState::Active.as_str()}).to_string();
+
+        let registries: Vec<Record<RevocationRegistry>> = Records::search(self.wallet_handle, REGISTRY_RECORD, &query)?;
+
+        Ok(registries.into_iter().next().map(|record| record.value))
+    }
+
+    /// Returns a registry of the credential definition.
+    pub fn registry(&self, rev_reg_id: &str) -> Result<Option<RevocationRegistry>, ErrorCode> {
+        let registry: Option<Record<RevocationRegistry>> = Records::get(self.wallet_handle, REGISTRY_RECORD, rev_reg_id)?;
+
+        Ok(registry.map(|record| record.value).filter(|registry| registry.cred_def_id == self.cred_def_id))
+    }
+
+    /// Returns all registries of the credential definition.
+    pub fn registries(&self) -> Result<Vec<RevocationRegistry>, ErrorCode> {
+        let query = json!({"cred_def_id": self.cred_def_id}).to_string();
+
+        let registries: Vec<Record<RevocationRegistry>> = Records::search(self.wallet_handle, REGISTRY_RECORD, &query)?;
+
+        Ok(registries.into_iter().map(|record| record.value).collect())
+    }
+
+    /// Returns where a credential issued by `RevocationRegistryManager::issue_credential` lives.
+    pub fn credential_location(&self, cred_id: &str) -> Result<Option<CredentialLocation>, ErrorCode> {
+        let location: Option<Record<CredentialLocation>> = Records::get(self.wallet_handle, CREDENTIAL_RECORD, cred_id)?;
+
+        Ok(location.map(|record| record.value))
+    }
+
+    /// Revokes credentials and publishes one merged revocation registry entry per registry.
+    ///
+    /// A credential that can not be revoked does not stop the others. A delta that can not be
+    /// merged with the previous ones of its registry is published as an entry of its own. If an
+    /// entry can not be published, all credentials revoked in it are reported as failed with the
+    /// error of the publication; they stay revoked in the wallet and the merged delta is returned in
+    /// `RevocationReport::unpublished` to publish later with `RevocationRegistryManager::publish_delta`.
+    ///
+    /// # Arguments
+    /// * `credentials` - registry and credential revocation ID of each credential to revoke.
+    ///
+    /// # Returns
+    /// The published entries and the credentials that were not revoked.
+    pub fn revoke_credentials(&self, credentials: &[CredentialLocation]) -> Result<RevocationReport, ErrorCode> {
+        let mut report = RevocationReport::default();
+
+        let mut by_registry: Vec<(&str, Vec<&CredentialLocation>)> = Vec::new();
+        for credential in credentials {
+            match by_registry.iter().position(|&(rev_reg_id, _)| rev_reg_id == credential.rev_reg_id) {
+                Some(index) => by_registry[index].1.push(credential),
+                None => by_registry.push((&credential.rev_reg_id, vec![credential]))
+            }
+        }
+
+        for (rev_reg_id, credentials) in by_registry {
+            let tails_reader_handle = self.registry(rev_reg_id)
+                .and_then(|registry| registry.ok_or(ErrorCode::WalletItemNotFound))
+                .and_then(|registry| registry.open_tails_reader());
+
+            let tails_reader_handle = match tails_reader_handle {
+                Ok(handle) => handle,
+                Err(err) => {
+                    report.fail(credentials, err);
+                    continue;
+                }
+            };
+
+            // Revoked credentials and their merged delta, one entry to publish each.
+            let mut entries: Vec<(Vec<&CredentialLocation>, String)> = Vec::new();
+
+            for credential in credentials {
+                let delta = match Issuer::revoke_credential(self.wallet_handle, tails_reader_handle, rev_reg_id, &credential.cred_rev_id) {
+                    Ok(delta) => delta,
+                    Err(err) => {
+                        report.fail(vec![credential], err);
+                        continue;
+                    }
+                };
+
+                let merged = entries.last().map(|(_, merged)| Issuer::merge_revocation_registry_deltas(merged, &delta));
+
+                match merged {
+                    Some(Ok(merged)) => {
+                        let entry = entries.last_mut().unwrap();
+                        entry.0.push(credential);
+                        entry.1 = merged;
+                    }
+                    Some(Err(_)) => {
+                        warn!("Failed to merge revocation registry delta of {} for {}", credential.cred_rev_id, rev_reg_id);
+                        entries.push((vec![credential], delta));
+                    }
+                    None => entries.push((vec![credential], delta))
+                }
+            }
+
+            for (revoked, rev_reg_delta_json) in entries {
+                match self.publish_delta(rev_reg_id, &rev_reg_delta_json) {
+                    Ok(()) => {
+                        report.revoked.extend(revoked.into_iter().cloned());
+                        report.published.entry(rev_reg_id.to_string()).or_insert_with(Vec::new).push(rev_reg_delta_json);
+                    }
+                    Err(err) => {
+                        warn!("Failed to publish revocation registry entry for {}", rev_reg_id);
+                        report.fail(revoked, err);
+                        report.unpublished.entry(rev_reg_id.to_string()).or_insert_with(Vec::new).push(rev_reg_delta_json);
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Publishes a revocation registry entry, e.g. a delta of `RevocationReport::unpublished`.
+    ///
+    /// # Arguments
+    /// * `rev_reg_id` - id of the revocation registry.
+    /// * `rev_reg_delta_json` - revocation registry delta to publish.
+    pub fn publish_delta(&self, rev_reg_id: &str, rev_reg_delta_json: &str) -> Result<(), ErrorCode> {
+        let request = Ledger::build_revoc_reg_entry_request(&self.issuer_did, rev_reg_id, REVOC_DEF_TYPE, rev_reg_delta_json)?;
+        self.publish(&request)
+    }
+
+    fn issue_into(&self, registry: &RevocationRegistry, cred_offer_json: &str, cred_req_json: &str, cred_values_json: &str) -> Result<(String, CredentialLocation, Option<String>), ErrorCode> {
+        let tails_reader_handle = registry.open_tails_reader()?;
+
+        let (cred_json, cred_rev_id, rev_reg_delta_json) =
+            Issuer::create_credential(self.wallet_handle, cred_offer_json, cred_req_json, cred_values_json, Some(&registry.rev_reg_id), tails_reader_handle)?;
+
+        let cred_rev_id = cred_rev_id.ok_or(ErrorCode::CommonInvalidState)?;
+
+        Ok((cred_json, CredentialLocation { rev_reg_id: registry.rev_reg_id.clone(), cred_rev_id }, rev_reg_delta_json))
+    }
+
+    fn publish(&self, request: &str) -> Result<(), ErrorCode> {
+        let response = Ledger::sign_and_submit_request(self.pool_handle, self.wallet_handle, &self.issuer_did, request)?;
+        ReplyHandler::check(&response).map(|_| ())
+    }
+
+    fn set_state(&self, rev_reg_id: &str, state: RevocationRegistryState) -> Result<(), ErrorCode> {
+        let mut registry: RevocationRegistry = Records::get(self.wallet_handle, REGISTRY_RECORD, rev_reg_id)?
+            .ok_or(ErrorCode::WalletItemNotFound)?
+            .value;
+        registry.state = state;
+
+        Records::update(self.wallet_handle, REGISTRY_RECORD, rev_reg_id, &registry)?;
+        Records::update_tags(self.wallet_handle, REGISTRY_RECORD, rev_reg_id, &self.registry_tags(state))
+    }
+
+    fn registry_tags(&self, state: RevocationRegistryState) -> HashMap<String, String> {
+        let mut tags = HashMap::new();
+        tags.insert("cred_def_id".to_string(), self.cred_def_id.clone());
+        tags.insert("state".to_string(), state.as_str().to_string());
+        tags
+    }
+}
@@ -0,0 +1,192 @@
+use {ErrorCode, IndyHandle};
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anoncreds::AnonCreds;
+use auto_prover::{CredentialInfo, NonRevokedInterval};
+use blob_storage::Blob;
+use revocation::{RevocationRegistry, TAILS_TYPE};
+use utils::fetch::Fetch;
+use utils::records::{Record, Records};
+
+const STATE_RECORD: &'static str = "IndyWrapper::RevocationState";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedStates {
+    rev_reg_id: String,
+    rev_reg_def_json: String,
+    /// Sorted by timestamp, oldest first.
+    states: Vec<CachedState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedState {
+    timestamp: u64,
+    rev_state_json: String,
+}
+
+/// Revocation states of the credentials of a holder, kept in non-secret wallet records.
+///
+/// A new state is built from the newest cached state older than the requested interval with
+/// the registry delta since its timestamp, so the whole delta and tails file are only read for
+/// the first state of a credential.
+pub struct RevocationStateCache {}
+
+impl RevocationStateCache {
+    /// Number of states kept per credential, older states are dropped.
+    pub const MAX_STATES: usize = 10;
+
+    /// Returns a revocation state of a credential for the interval.
+    ///
+    /// The newest cached state within the interval is returned as is. Otherwise a state for the
+    /// end of the interval (now if it is open) is built from the ledger and cached. An interval
+    /// without start only matches a cached state at its end, an older one may be stale.
+    ///
+    /// # Arguments
+    /// * `pool_handle` - pool handle (created by Pool::open_ledger).
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `cred_info` - the credential as returned by Prover::get_credentials_for_proof_req.
+    /// * `interval` - the interval the credential has to be non-revoked in.
+    /// * `tails_dir` - directory with the tails file of the revocation registry.
+    ///
+    /// # Returns
+    /// * `rev_state_json` - revocation state json (see AnonCreds::create_revocation_state).
+    /// * `timestamp` - the timestamp of the state.
+    /// `CommonInvalidStructure` if the credential is not revocable.
+    pub fn get(pool_handle: IndyHandle, wallet_handle: IndyHandle, cred_info: &CredentialInfo, interval: &NonRevokedInterval, tails_dir: &str) -> Result<(String, u64), ErrorCode> {
+        let (rev_reg_id, cred_rev_id) = match (cred_info.rev_reg_id.as_ref(), cred_info.cred_rev_id.as_ref()) {
+            (Some(rev_reg_id), Some(cred_rev_id)) => (rev_reg_id, cred_rev_id),
+            _ => return Err(ErrorCode::CommonInvalidStructure)
+        };
+
+        let to = interval.to.unwrap_or_else(RevocationStateCache::now);
+
+        let cached: Option<Record<CachedStates>> = Records::get(wallet_handle, STATE_RECORD, &cred_info.referent)?;
+        let exists = cached.is_some();
+
+        let mut cached = match cached {
+            Some(record) => record.value,
+            None => CachedStates {
+                rev_reg_id: rev_reg_id.clone(),
+                rev_reg_def_json: Fetch::rev_reg_def(pool_handle, None, rev_reg_id)?,
+                states: Vec::new(),
+            }
+        };
+
+        if let Some(state) = RevocationStateCache::within(&cached.states, interval.from, to) {
+            return Ok((state.rev_state_json.clone(), state.timestamp));
+        }
+
+        let tails_reader_handle = Blob::open_reader(TAILS_TYPE, &RevocationRegistry::tails_config(tails_dir))?;
+
+        let state = match RevocationStateCache::before(&cached.states, to) {
+            Some(base) => {
+                let (rev_reg_delta_json, timestamp) = Fetch::rev_reg_delta(pool_handle, None, rev_reg_id, base.timestamp as i64, to as i64)?;
+                let rev_state_json = AnonCreds::update_revocation_state(tails_reader_handle, &base.rev_state_json, &cached.rev_reg_def_json, &rev_reg_delta_json, timestamp, cred_rev_id)?;
+
+                CachedState { timestamp, rev_state_json }
+            }
+            None => {
+                let (rev_reg_delta_json, timestamp) = Fetch::rev_reg_delta(pool_handle, None, rev_reg_id, -1, to as i64)?;
+                let rev_state_json = AnonCreds::create_revocation_state(tails_reader_handle, &cached.rev_reg_def_json, &rev_reg_delta_json, timestamp, cred_rev_id)?;
+
+                CachedState { timestamp, rev_state_json }
+            }
+        };
+
+        let result = (state.rev_state_json.clone(), state.timestamp);
+
+        RevocationStateCache::insert(&mut cached.states, state);
+
+        if exists {
+            Records::update(wallet_handle, STATE_RECORD, &cred_info.referent, &cached)?;
+        } else {
+            let mut tags = HashMap::new();
+            tags.insert("rev_reg_id".to_string(), cached.rev_reg_id.clone());
+            Records::add(wallet_handle, STATE_RECORD, &cred_info.referent, &cached, &tags)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Newest state with a timestamp in `[from, to]`, the state at `to` if there is no `from`.
+    fn within(states: &[CachedState], from: Option<u64>, to: u64) -> Option<&CachedState> {
+        let from = from.unwrap_or(to);
+
+        states.iter().rev().find(|state| state.timestamp <= to && state.timestamp >= from)
+    }
+
+    /// Newest state not newer than `to`, the base for a state at `to`.
+    fn before(states: &[CachedState], to: u64) -> Option<&CachedState> {
+        states.iter().rev().find(|state| state.timestamp <= to)
+    }
+
+    fn insert(states: &mut Vec<CachedState>, state: CachedState) {
+        states.retain(|cached| cached.timestamp != state.timestamp);
+
+        let index = states.iter().position(|cached| cached.timestamp > state.timestamp).unwrap_or(states.len());
+        states.insert(index, state);
+
+        if states.len() > RevocationStateCache::MAX_STATES {
+            let excess = states.len() - RevocationStateCache::MAX_STATES;
+            states.drain(..excess);
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn states(timestamps: &[u64]) -> Vec<CachedState> {
+        timestamps.iter().map(|&timestamp| CachedState { timestamp, rev_state_json: timestamp.to_string() }).collect()
+    }
+
+    #[test]
+    fn within_picks_newest_state_in_interval() {
+        let states = states(&[10, 20, 30]);
+
+        assert_eq!(RevocationStateCache::within(&states, Some(15), 40).unwrap().timestamp, 30);
+        assert_eq!(RevocationStateCache::within(&states, Some(0), 25).unwrap().timestamp, 20);
+        assert!(RevocationStateCache::within(&states, Some(35), 40).is_none());
+        assert!(RevocationStateCache::within(&states, None, 5).is_none());
+    }
+
+    #[test]
+    fn within_without_from_only_matches_state_at_to() {
+        let states = states(&[10, 20, 30]);
+
+        assert_eq!(RevocationStateCache::within(&states, None, 20).unwrap().timestamp, 20);
+        assert!(RevocationStateCache::within(&states, None, 25).is_none());
+        assert!(RevocationStateCache::within(&states, None, 40).is_none());
+    }
+
+    #[test]
+    fn before_picks_newest_base() {
+        let states = states(&[10, 20, 30]);
+
+        assert_eq!(RevocationStateCache::before(&states, 40).unwrap().timestamp, 30);
+        assert_eq!(RevocationStateCache::before(&states, 15).unwrap().timestamp, 10);
+        assert!(RevocationStateCache::before(&states, 5).is_none());
+    }
+
+    #[test]
+    fn insert_keeps_states_sorted_and_bounded() {
+        let mut cached = states(&[10, 30]);
+
+        RevocationStateCache::insert(&mut cached, CachedState { timestamp: 20, rev_state_json: "new".to_string() });
+        RevocationStateCache::insert(&mut cached, CachedState { timestamp: 30, rev_state_json: "new".to_string() });
+        assert_eq!(cached.iter().map(|state| state.timestamp).collect::<Vec<u64>>(), vec![10, 20, 30]);
+        assert_eq!(cached[2].rev_state_json, "new");
+
+        let mut cached = states(&(0..RevocationStateCache::MAX_STATES as u64).collect::<Vec<u64>>());
+        RevocationStateCache::insert(&mut cached, CachedState { timestamp: 100, rev_state_json: "new".to_string() });
+        assert_eq!(cached.len(), RevocationStateCache::MAX_STATES);
+        assert_eq!(cached[0].timestamp, 1);
+    }
+}
@@ -0,0 +1,226 @@
+use {ErrorCode, IndyHandle};
+
+use serde_json;
+use serde_json::Value;
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crypto::Crypto;
+use did::Did;
+use pairwise::Pairwise;
+use utils::base64::Base64;
+use utils::records::{Record, Records};
+
+const CHANNEL_RECORD: &'static str = "IndyWrapper::SecureChannel";
+
+/// Sequence numbers of a channel, kept in the wallet so they survive restarts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct ChannelState {
+    /// Sequence number of the last sent message.
+    sent: u64,
+    /// Sequence number of the last accepted message.
+    received: u64,
+}
+
+/// Message as it is auth-crypted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Envelope {
+    from: String,
+    to: String,
+    thread_id: String,
+    seq: u64,
+    timestamp: u64,
+    /// The message (base64).
+    body: String,
+}
+
+/// Message accepted by SecureChannel::receive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedMessage {
+    pub thread_id: String,
+    pub seq: u64,
+    /// Time the message was sent, in seconds from Unix Epoch.
+    pub timestamp: u64,
+    pub body: Vec<u8>,
+}
+
+/// Authenticated channel to the other party of a pairwise.
+///
+/// Messages are auth-crypted with the verkeys of the pairwise DIDs and carry the DIDs, a thread
+/// id, a sequence number and a timestamp. `SecureChannel::receive` only accepts messages of the
+/// pairwise verkey with a sequence number above the last accepted one and a timestamp within
+/// `SecureChannel::MAX_CLOCK_SKEW_SECS`, so replayed and reordered messages are rejected.
+///
+/// The verkeys are read from the wallet for every message, so a channel keeps working when
+/// either DID rotates its keys.
+pub struct SecureChannel {
+    wallet_handle: IndyHandle,
+    pub my_did: String,
+    pub their_did: String,
+}
+
+impl SecureChannel {
+    /// Maximum difference in seconds between the timestamp of a received message and now.
+    pub const MAX_CLOCK_SKEW_SECS: u64 = 300;
+
+    /// Opens the channel to the other party of a pairwise.
+    ///
+    /// # Arguments
+    /// * `wallet_handle` - wallet handle (created by Wallet::open).
+    /// * `their_did` - DID of the other party (see Pairwise::create).
+    pub fn open(wallet_handle: IndyHandle, their_did: &str) -> Result<SecureChannel, ErrorCode> {
+        let pairwise: Value = serde_json::from_str(&Pairwise::get(wallet_handle, their_did)?).map_err(|_| ErrorCode::CommonInvalidState)?;
+        let my_did = pairwise["my_did"].as_str().ok_or(ErrorCode::CommonInvalidState)?.to_string();
+
+        Ok(SecureChannel {
+            wallet_handle,
+            my_did,
+            their_did: their_did.to_string(),
+        })
+    }
+
+    /// Wraps a message with the next sequence number and encrypts it for the other party.
+    ///
+    /// # Arguments
+    /// * `thread_id` - id of the conversation the message belongs to.
+    /// * `message` - the message.
+    ///
+    /// # Returns
+    /// The encrypted message.
+    pub fn send(&self, thread_id: &str, message: &[u8]) -> Result<Vec<u8>, ErrorCode> {
+        let (mut state, exists) = self.state()?;
+        state.sent += 1;
+
+        let envelope = Envelope {
+            from: self.my_did.clone(),
+            to: self.their_did.clone(),
+            thread_id: thread_id.to_string(),
+            seq: state.sent,
+            timestamp: SecureChannel::now(),
+            body: Base64::encode(message),
+        };
+        let envelope = serde_json::to_vec(&envelope).map_err(|_| ErrorCode::CommonInvalidState)?;
+
+        let my_verkey = Did::get_ver_key_local(self.wallet_handle, &self.my_did)?;
+        let their_verkey = Did::get_ver_key_local(self.wallet_handle, &self.their_did)?;
+        let encrypted = Crypto::auth_crypt(self.wallet_handle, &my_verkey, &their_verkey, &envelope)?;
+
+        self.store_state(&state, exists)?;
+
+        Ok(encrypted)
+    }
+
+    /// Decrypts and checks a message of the other party.
+    ///
+    /// # Arguments
+    /// * `encrypted_message` - message encrypted by SecureChannel::send of the other party.
+    ///
+    /// # Returns
+    /// The message. `CommonInvalidStructure` if it was not sent by the other party of the
+    /// pairwise and `CommonInvalidState` if it is a replay, out of order or outside of
+    /// `SecureChannel::MAX_CLOCK_SKEW_SECS`.
+    pub fn receive(&self, encrypted_message: &[u8]) -> Result<ReceivedMessage, ErrorCode> {
+        let my_verkey = Did::get_ver_key_local(self.wallet_handle, &self.my_did)?;
+        let their_verkey = Did::get_ver_key_local(self.wallet_handle, &self.their_did)?;
+
+        let (sender_verkey, envelope) = Crypto::auth_decrypt(self.wallet_handle, &my_verkey, encrypted_message)?;
+        let envelope: Envelope = serde_json::from_slice(&envelope).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        if sender_verkey != their_verkey || envelope.from != self.their_did || envelope.to != self.my_did {
+            warn!("Message was not sent by {} to {}", self.their_did, self.my_did);
+            return Err(ErrorCode::CommonInvalidStructure);
+        }
+
+        let body = Base64::decode(&envelope.body).ok_or(ErrorCode::CommonInvalidStructure)?;
+
+        let (mut state, exists) = self.state()?;
+
+        SecureChannel::check(&state, &envelope, SecureChannel::now())?;
+
+        state.received = envelope.seq;
+        self.store_state(&state, exists)?;
+
+        Ok(ReceivedMessage { thread_id: envelope.thread_id, seq: envelope.seq, timestamp: envelope.timestamp, body })
+    }
+
+    fn check(state: &ChannelState, envelope: &Envelope, now: u64) -> Result<(), ErrorCode> {
+        if envelope.seq <= state.received {
+            warn!("Message {} is a replay or out of order, last accepted message is {}", envelope.seq, state.received);
+            return Err(ErrorCode::CommonInvalidState);
+        }
+
+        if envelope.timestamp.saturating_add(SecureChannel::MAX_CLOCK_SKEW_SECS) < now || envelope.timestamp > now.saturating_add(SecureChannel::MAX_CLOCK_SKEW_SECS) {
+            warn!("Timestamp {} of message {} is too far from now", envelope.timestamp, envelope.seq);
+            return Err(ErrorCode::CommonInvalidState);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the state of the channel and whether it is stored already.
+    fn state(&self) -> Result<(ChannelState, bool), ErrorCode> {
+        let record: Option<Record<ChannelState>> = Records::get(self.wallet_handle, CHANNEL_RECORD, &self.their_did)?;
+
+        Ok(match record {
+            Some(record) => (record.value, true),
+            None => (ChannelState::default(), false)
+        })
+    }
+
+    fn store_state(&self, state: &ChannelState, exists: bool) -> Result<(), ErrorCode> {
+        if exists {
+            Records::update(self.wallet_handle, CHANNEL_RECORD, &self.their_did, state)
+        } else {
+            Records::add(self.wallet_handle, CHANNEL_RECORD, &self.their_did, state, &HashMap::new())
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).map(|now| now.as_secs()).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOW: u64 = 1_000_000;
+
+    fn envelope(seq: u64, timestamp: u64) -> Envelope {
+        Envelope { from: "their".to_string(), to: "my".to_string(), thread_id: "thread".to_string(), seq, timestamp, body: String::new() }
+    }
+
+    #[test]
+    fn check_accepts_next_messages() {
+        let state = ChannelState { sent: 0, received: 2 };
+
+        SecureChannel::check(&state, &envelope(3, NOW), NOW).unwrap();
+        SecureChannel::check(&state, &envelope(10, NOW - SecureChannel::MAX_CLOCK_SKEW_SECS), NOW).unwrap();
+    }
+
+    #[test]
+    fn check_rejects_replay_and_reordering() {
+        let state = ChannelState { sent: 0, received: 2 };
+
+        assert_eq!(SecureChannel::check(&state, &envelope(2, NOW), NOW).unwrap_err(), ErrorCode::CommonInvalidState);
+        assert_eq!(SecureChannel::check(&state, &envelope(1, NOW), NOW).unwrap_err(), ErrorCode::CommonInvalidState);
+    }
+
+    #[test]
+    fn check_rejects_stale_and_future_messages() {
+        let state = ChannelState::default();
+
+        assert_eq!(SecureChannel::check(&state, &envelope(1, NOW - SecureChannel::MAX_CLOCK_SKEW_SECS - 1), NOW).unwrap_err(), ErrorCode::CommonInvalidState);
+        assert_eq!(SecureChannel::check(&state, &envelope(1, NOW + SecureChannel::MAX_CLOCK_SKEW_SECS + 1), NOW).unwrap_err(), ErrorCode::CommonInvalidState);
+    }
+
+    #[test]
+    fn check_handles_extreme_timestamps() {
+        let state = ChannelState::default();
+
+        assert_eq!(SecureChannel::check(&state, &envelope(1, u64::max_value()), NOW).unwrap_err(), ErrorCode::CommonInvalidState);
+        assert_eq!(SecureChannel::check(&state, &envelope(1, u64::max_value() - 1), NOW).unwrap_err(), ErrorCode::CommonInvalidState);
+        SecureChannel::check(&state, &envelope(1, u64::max_value()), u64::max_value()).unwrap();
+    }
+}
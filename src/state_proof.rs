@@ -0,0 +1,156 @@
+use ErrorCode;
+
+use native::{CString, Error};
+
+use serde_json;
+
+use std::ffi::{self, CStr};
+use std::panic::{self, AssertUnwindSafe};
+
+/// State proof extracted from a node reply, checked by libindy against the pool state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedSP {
+    /// Encoded (base64) serialized trie nodes of the proof.
+    pub proof_nodes: String,
+    /// Root hash of the trie (base58).
+    pub root_hash: String,
+    /// Keys and values the proof has to contain.
+    pub kvs_to_verify: KeyValuesInSP,
+    /// `multi_signature` of the reply.
+    pub multi_signature: serde_json::Value,
+}
+
+/// Keys and values to check in a state proof.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum KeyValuesInSP {
+    /// Each key (base64) maps to the given value, `None` means the key is absent.
+    Simple(KeyValueSimpleData),
+    /// The listed key-value pairs are the whole sub-trie below `sub_trie_prefix` (base64).
+    SubTrie(KeyValuesSubTrieData),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyValueSimpleData {
+    pub kvs: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyValuesSubTrieData {
+    pub sub_trie_prefix: Option<String>,
+    pub kvs: Vec<(String, Option<String>)>,
+}
+
+/// Parser of state proofs for custom ledger transactions, registered with
+/// `Ledger::register_state_proof_parser::<T>(txn_type)`.
+///
+/// libindy calls it for replies to requests of the registered `txn_type` and verifies
+/// the returned proofs. The crate serializes the result and frees it once libindy is done.
+pub trait StateProofParser {
+    /// Extracts the state proofs from the reply of a node.
+    fn parse(reply_from_node: &str) -> Result<Vec<ParsedSP>, ErrorCode>;
+}
+
+pub(crate) extern "C" fn parse<T: StateProofParser>(reply_from_node: CString, parsed_sp: *mut CString) -> Error {
+    // A panicking parser must not unwind into libindy.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        if reply_from_node.is_null() {
+            return Err(ErrorCode::CommonInvalidParam1);
+        }
+
+        let reply_from_node = unsafe { CStr::from_ptr(reply_from_node) }.to_str().map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        let parsed = T::parse(reply_from_node)?;
+        let parsed = serde_json::to_string(&parsed).map_err(|_| ErrorCode::CommonInvalidState)?;
+
+        ffi::CString::new(parsed).map_err(|_| ErrorCode::CommonInvalidState)
+    })).unwrap_or_else(|_| {
+        error!("State proof parser panicked");
+        Err(ErrorCode::CommonInvalidState)
+    });
+
+    match result {
+        Ok(parsed) => {
+            unsafe { *parsed_sp = parsed.into_raw(); }
+            ErrorCode::Success as Error
+        }
+        Err(err) => err as Error
+    }
+}
+
+pub(crate) extern "C" fn free(data: CString) -> Error {
+    if !data.is_null() {
+        drop(unsafe { ffi::CString::from_raw(data as *mut _) });
+    }
+
+    ErrorCode::Success as Error
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::ptr::null;
+
+    struct TestParser {}
+
+    impl StateProofParser for TestParser {
+        fn parse(reply_from_node: &str) -> Result<Vec<ParsedSP>, ErrorCode> {
+            let reply: serde_json::Value = serde_json::from_str(reply_from_node).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+            Ok(vec![ParsedSP {
+                proof_nodes: reply["state_proof"]["proof_nodes"].as_str().unwrap_or_default().to_string(),
+                root_hash: reply["state_proof"]["root_hash"].as_str().unwrap_or_default().to_string(),
+                kvs_to_verify: KeyValuesInSP::Simple(KeyValueSimpleData { kvs: vec![("a2V5".to_string(), Some("value".to_string()))] }),
+                multi_signature: reply["state_proof"]["multi_signature"].clone(),
+            }])
+        }
+    }
+
+    struct PanickingParser {}
+
+    impl StateProofParser for PanickingParser {
+        fn parse(_reply_from_node: &str) -> Result<Vec<ParsedSP>, ErrorCode> {
+            panic!("parser failed")
+        }
+    }
+
+    #[test]
+    fn parsed_sp_serializes_type_tag() {
+        let kvs = KeyValuesInSP::SubTrie(KeyValuesSubTrieData { sub_trie_prefix: Some("cHJlZml4".to_string()), kvs: vec![] });
+
+        assert_eq!(serde_json::to_value(&kvs).unwrap(),
+                   json!({"type": "SubTrie", "sub_trie_prefix": "cHJlZml4", "kvs": []}));
+    }
+
+    #[test]
+    fn parse_and_free_work() {
+        let reply = ffi::CString::new(r#"{"state_proof": {"proof_nodes": "nodes", "root_hash": "hash", "multi_signature": {}}}"#).unwrap();
+        let mut parsed_sp: CString = null();
+
+        assert_eq!(parse::<TestParser>(reply.as_ptr(), &mut parsed_sp), ErrorCode::Success as Error);
+
+        let parsed: Vec<ParsedSP> = serde_json::from_str(unsafe { CStr::from_ptr(parsed_sp) }.to_str().unwrap()).unwrap();
+        assert_eq!(parsed[0].root_hash, "hash");
+        assert_eq!(parsed[0].kvs_to_verify, KeyValuesInSP::Simple(KeyValueSimpleData { kvs: vec![("a2V5".to_string(), Some("value".to_string()))] }));
+
+        assert_eq!(free(parsed_sp), ErrorCode::Success as Error);
+    }
+
+    #[test]
+    fn parse_returns_parser_error() {
+        let reply = ffi::CString::new("not json").unwrap();
+        let mut parsed_sp: CString = null();
+
+        assert_eq!(parse::<TestParser>(reply.as_ptr(), &mut parsed_sp), ErrorCode::CommonInvalidStructure as Error);
+        assert!(parsed_sp.is_null());
+    }
+    #[test]
+    fn parse_catches_parser_panic() {
+        let reply = ffi::CString::new("{}").unwrap();
+        let mut parsed_sp: CString = null();
+
+        assert_eq!(parse::<PanickingParser>(reply.as_ptr(), &mut parsed_sp), ErrorCode::CommonInvalidState as Error);
+        assert!(parsed_sp.is_null());
+    }
+}
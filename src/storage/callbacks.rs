@@ -0,0 +1,465 @@
+use ErrorCode;
+
+use native::{BString, CString, Error, Handle};
+use utils::sequence::SequenceUtils;
+
+use serde_json;
+
+use std::collections::HashMap;
+use std::ffi::{self, CStr};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr::null;
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+use super::{WalletStorage, StorageRecord, StorageSearch, RecordOptions, SearchOptions, Query, Tags};
+
+/// Record handed out to libindy. The strings stay alive until libindy frees the record.
+struct RecordEntry {
+    id: ffi::CString,
+    type_: Option<ffi::CString>,
+    value: Option<Vec<u8>>,
+    tags: Option<ffi::CString>,
+}
+
+impl RecordEntry {
+    fn new(record: StorageRecord) -> Result<RecordEntry, ErrorCode> {
+        let tags = match record.tags {
+            Some(ref tags) => Some(serde_json::to_string(tags).map_err(|_| ErrorCode::WalletStorageError)?),
+            None => None
+        };
+
+        Ok(RecordEntry {
+            id: to_c_string(record.id)?,
+            type_: opt_to_c_string(record.type_)?,
+            value: record.value,
+            tags: opt_to_c_string(tags)?,
+        })
+    }
+}
+
+lazy_static! {
+    static ref STORAGES: Mutex<HashMap<Handle, Arc<Mutex<Box<WalletStorage>>>>> = Default::default();
+    static ref RECORDS: Mutex<HashMap<Handle, RecordEntry>> = Default::default();
+    static ref SEARCHES: Mutex<HashMap<Handle, StorageSearch>> = Default::default();
+    static ref METADATA: Mutex<HashMap<Handle, ffi::CString>> = Default::default();
+}
+
+fn to_c_string(s: String) -> Result<ffi::CString, ErrorCode> {
+    ffi::CString::new(s).map_err(|_| ErrorCode::WalletStorageError)
+}
+
+fn opt_to_c_string(s: Option<String>) -> Result<Option<ffi::CString>, ErrorCode> {
+    match s {
+        Some(s) => to_c_string(s).map(Some),
+        None => Ok(None)
+    }
+}
+
+/// Runs the body of a callback. Panics (e.g. of a `WalletStorage` implementation) must not
+/// unwind into libindy, they are returned as `CommonInvalidState`.
+fn guard<F>(f: F) -> Error where F: FnOnce() -> Result<(), ErrorCode> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => ErrorCode::Success as Error,
+        Ok(Err(err)) => err as Error,
+        Err(_) => {
+            error!("Wallet storage callback panicked");
+            ErrorCode::CommonInvalidState as Error
+        }
+    }
+}
+
+fn string_arg(arg: CString) -> Result<String, ErrorCode> {
+    opt_string_arg(arg)?.ok_or(ErrorCode::WalletInputError)
+}
+
+fn opt_string_arg(arg: CString) -> Result<Option<String>, ErrorCode> {
+    if arg.is_null() {
+        return Ok(None);
+    }
+
+    unsafe { CStr::from_ptr(arg) }.to_str()
+        .map(|s| Some(s.to_string()))
+        .map_err(|_| ErrorCode::WalletInputError)
+}
+
+fn tags_arg(tags_json: CString) -> Result<Tags, ErrorCode> {
+    match opt_string_arg(tags_json)? {
+        Some(ref tags_json) if !tags_json.is_empty() => serde_json::from_str(tags_json).map_err(|_| ErrorCode::WalletInputError),
+        _ => Ok(Tags::new())
+    }
+}
+
+fn value_arg(value: BString, value_len: usize) -> Result<Vec<u8>, ErrorCode> {
+    if value.is_null() {
+        return if value_len == 0 { Ok(Vec::new()) } else { Err(ErrorCode::WalletInputError) };
+    }
+
+    Ok(unsafe { slice::from_raw_parts(value, value_len) }.to_vec())
+}
+
+fn with_storage<F, R>(storage_handle: Handle, f: F) -> Result<R, ErrorCode> where F: FnOnce(&mut WalletStorage) -> Result<R, ErrorCode> {
+    let storage = STORAGES.lock().unwrap()
+        .get(&storage_handle)
+        .cloned()
+        .ok_or(ErrorCode::WalletInvalidHandle)?;
+
+    let mut storage = storage.lock().unwrap();
+
+    f(storage.as_mut())
+}
+
+fn with_record<F>(record_handle: Handle, f: F) -> Result<(), ErrorCode> where F: FnOnce(&RecordEntry) -> Result<(), ErrorCode> {
+    let records = RECORDS.lock().unwrap();
+
+    match records.get(&record_handle) {
+        Some(record) => f(record),
+        None => Err(ErrorCode::WalletInvalidHandle)
+    }
+}
+
+fn add_record_entry(record: StorageRecord) -> Result<Handle, ErrorCode> {
+    let record = RecordEntry::new(record)?;
+    let record_handle = SequenceUtils::get_next_id();
+
+    RECORDS.lock().unwrap().insert(record_handle, record);
+
+    Ok(record_handle)
+}
+
+fn add_search(search: StorageSearch) -> Handle {
+    let search_handle = SequenceUtils::get_next_id();
+
+    SEARCHES.lock().unwrap().insert(search_handle, search);
+
+    search_handle
+}
+
+pub extern "C" fn create<T: WalletStorage + 'static>(id: CString,
+                                                 config: CString,
+                                                 credentials: CString,
+                                                 metadata: CString) -> Error {
+    guard(|| {
+        let id = string_arg(id)?;
+        let config = opt_string_arg(config)?;
+        let credentials = opt_string_arg(credentials)?;
+        let metadata = string_arg(metadata)?;
+
+        T::create(&id, config.as_ref().map(String::as_str), credentials.as_ref().map(String::as_str), &metadata)
+    })
+}
+
+pub extern "C" fn open<T: WalletStorage + 'static>(id: CString,
+                                               config: CString,
+                                               credentials: CString,
+                                               storage_handle_p: *mut Handle) -> Error {
+    guard(|| {
+        let id = string_arg(id)?;
+        let config = opt_string_arg(config)?;
+        let credentials = opt_string_arg(credentials)?;
+
+        let storage = T::open(&id, config.as_ref().map(String::as_str), credentials.as_ref().map(String::as_str))?;
+        let storage_handle = SequenceUtils::get_next_id();
+
+        STORAGES.lock().unwrap().insert(storage_handle, Arc::new(Mutex::new(Box::new(storage))));
+
+        unsafe { *storage_handle_p = storage_handle; }
+        Ok(())
+    })
+}
+
+pub extern "C" fn close(storage_handle: Handle) -> Error {
+    guard(|| {
+        let storage = STORAGES.lock().unwrap()
+            .remove(&storage_handle)
+            .ok_or(ErrorCode::WalletInvalidHandle)?;
+
+        let mut storage = storage.lock().unwrap();
+        storage.close()
+    })
+}
+
+pub extern "C" fn delete<T: WalletStorage + 'static>(id: CString,
+                                                 config: CString,
+                                                 credentials: CString) -> Error {
+    guard(|| {
+        let id = string_arg(id)?;
+        let config = opt_string_arg(config)?;
+        let credentials = opt_string_arg(credentials)?;
+
+        T::delete(&id, config.as_ref().map(String::as_str), credentials.as_ref().map(String::as_str))
+    })
+}
+
+pub extern "C" fn add_record(storage_handle: Handle,
+                         type_: CString,
+                         id: CString,
+                         value: BString,
+                         value_len: usize,
+                         tags_json: CString) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let id = string_arg(id)?;
+        let value = value_arg(value, value_len)?;
+        let tags = tags_arg(tags_json)?;
+
+        with_storage(storage_handle, |storage| storage.add_record(&type_, &id, &value, &tags))
+    })
+}
+
+pub extern "C" fn update_record_value(storage_handle: Handle,
+                                  type_: CString,
+                                  id: CString,
+                                  value: BString,
+                                  value_len: usize) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let id = string_arg(id)?;
+        let value = value_arg(value, value_len)?;
+
+        with_storage(storage_handle, |storage| storage.update_record_value(&type_, &id, &value))
+    })
+}
+
+pub extern "C" fn update_record_tags(storage_handle: Handle,
+                                 type_: CString,
+                                 id: CString,
+                                 tags_json: CString) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let id = string_arg(id)?;
+        let tags = tags_arg(tags_json)?;
+
+        with_storage(storage_handle, |storage| storage.update_record_tags(&type_, &id, &tags))
+    })
+}
+
+pub extern "C" fn add_record_tags(storage_handle: Handle,
+                              type_: CString,
+                              id: CString,
+                              tags_json: CString) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let id = string_arg(id)?;
+        let tags = tags_arg(tags_json)?;
+
+        with_storage(storage_handle, |storage| storage.add_record_tags(&type_, &id, &tags))
+    })
+}
+
+pub extern "C" fn delete_record_tags(storage_handle: Handle,
+                                 type_: CString,
+                                 id: CString,
+                                 tag_names_json: CString) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let id = string_arg(id)?;
+        let tag_names: Vec<String> = serde_json::from_str(&string_arg(tag_names_json)?)
+            .map_err(|_| ErrorCode::WalletInputError)?;
+
+        with_storage(storage_handle, |storage| storage.delete_record_tags(&type_, &id, &tag_names))
+    })
+}
+
+pub extern "C" fn delete_record(storage_handle: Handle,
+                            type_: CString,
+                            id: CString) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let id = string_arg(id)?;
+
+        with_storage(storage_handle, |storage| storage.delete_record(&type_, &id))
+    })
+}
+
+pub extern "C" fn get_record(storage_handle: Handle,
+                         type_: CString,
+                         id: CString,
+                         options_json: CString,
+                         record_handle_p: *mut Handle) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let id = string_arg(id)?;
+        let options = match opt_string_arg(options_json)? {
+            Some(options_json) => RecordOptions::from_json(&options_json)?,
+            None => RecordOptions::default()
+        };
+
+        let record = with_storage(storage_handle, |storage| storage.get_record(&type_, &id, &options))?;
+        let record_handle = add_record_entry(record)?;
+
+        unsafe { *record_handle_p = record_handle; }
+        Ok(())
+    })
+}
+
+pub extern "C" fn get_record_id(_storage_handle: Handle,
+                            record_handle: Handle,
+                            record_id_p: *mut CString) -> Error {
+    guard(|| with_record(record_handle, |record| {
+        unsafe { *record_id_p = record.id.as_ptr(); }
+        Ok(())
+    }))
+}
+
+pub extern "C" fn get_record_type(_storage_handle: Handle,
+                              record_handle: Handle,
+                              record_type_p: *mut CString) -> Error {
+    guard(|| with_record(record_handle, |record| {
+        unsafe { *record_type_p = record.type_.as_ref().map_or(null(), |type_| type_.as_ptr()); }
+        Ok(())
+    }))
+}
+
+pub extern "C" fn get_record_value(_storage_handle: Handle,
+                               record_handle: Handle,
+                               record_value_p: *mut BString,
+                               record_value_len_p: *mut usize) -> Error {
+    guard(|| with_record(record_handle, |record| {
+        let (value, value_len) = record.value.as_ref().map_or((null(), 0), |value| (value.as_ptr(), value.len()));
+
+        unsafe {
+            *record_value_p = value;
+            *record_value_len_p = value_len;
+        }
+        Ok(())
+    }))
+}
+
+pub extern "C" fn get_record_tags(_storage_handle: Handle,
+                              record_handle: Handle,
+                              record_tags_p: *mut CString) -> Error {
+    guard(|| with_record(record_handle, |record| {
+        unsafe { *record_tags_p = record.tags.as_ref().map_or(null(), |tags| tags.as_ptr()); }
+        Ok(())
+    }))
+}
+
+pub extern "C" fn free_record(_storage_handle: Handle,
+                          record_handle: Handle) -> Error {
+    guard(|| RECORDS.lock().unwrap()
+        .remove(&record_handle)
+        .map(|_| ())
+        .ok_or(ErrorCode::WalletInvalidHandle))
+}
+
+pub extern "C" fn get_storage_metadata(storage_handle: Handle,
+                                   metadata_p: *mut CString,
+                                   metadata_handle_p: *mut Handle) -> Error {
+    guard(|| {
+        let metadata = with_storage(storage_handle, |storage| storage.get_storage_metadata())?;
+        let metadata = to_c_string(metadata)?;
+        let metadata_handle = SequenceUtils::get_next_id();
+
+        unsafe {
+            *metadata_p = metadata.as_ptr();
+            *metadata_handle_p = metadata_handle;
+        }
+
+        METADATA.lock().unwrap().insert(metadata_handle, metadata);
+        Ok(())
+    })
+}
+
+pub extern "C" fn set_storage_metadata(storage_handle: Handle,
+                                   metadata: CString) -> Error {
+    guard(|| {
+        let metadata = string_arg(metadata)?;
+
+        with_storage(storage_handle, |storage| storage.set_storage_metadata(&metadata))
+    })
+}
+
+pub extern "C" fn free_storage_metadata(_storage_handle: Handle,
+                                    metadata_handle: Handle) -> Error {
+    guard(|| METADATA.lock().unwrap()
+        .remove(&metadata_handle)
+        .map(|_| ())
+        .ok_or(ErrorCode::WalletInvalidHandle))
+}
+
+pub extern "C" fn search_records(storage_handle: Handle,
+                             type_: CString,
+                             query_json: CString,
+                             options_json: CString,
+                             search_handle_p: *mut Handle) -> Error {
+    guard(|| {
+        let type_ = string_arg(type_)?;
+        let query = Query::parse(&string_arg(query_json)?)?;
+        let options = match opt_string_arg(options_json)? {
+            Some(options_json) => SearchOptions::from_json(&options_json)?,
+            None => SearchOptions::default()
+        };
+
+        let search = with_storage(storage_handle, |storage| storage.search_records(&type_, &query, &options))?;
+
+        unsafe { *search_handle_p = add_search(search); }
+        Ok(())
+    })
+}
+
+pub extern "C" fn search_all_records(storage_handle: Handle,
+                                 search_handle_p: *mut Handle) -> Error {
+    guard(|| {
+        let search = with_storage(storage_handle, |storage| storage.search_all_records())?;
+
+        unsafe { *search_handle_p = add_search(search); }
+        Ok(())
+    })
+}
+
+pub extern "C" fn get_search_total_count(_storage_handle: Handle,
+                                     search_handle: Handle,
+                                     total_count_p: *mut usize) -> Error {
+    guard(|| {
+        let searches = SEARCHES.lock().unwrap();
+        let search = searches.get(&search_handle).ok_or(ErrorCode::WalletInvalidHandle)?;
+        let total_count = search.total_count().ok_or(ErrorCode::WalletStorageError)?;
+
+        unsafe { *total_count_p = total_count; }
+        Ok(())
+    })
+}
+
+/// libindy treats `WalletItemNotFound` as the end of the search.
+pub extern "C" fn fetch_search_next_record(_storage_handle: Handle,
+                                       search_handle: Handle,
+                                       record_handle_p: *mut Handle) -> Error {
+    guard(|| {
+        let record = {
+            let mut searches = SEARCHES.lock().unwrap();
+            let search = searches.get_mut(&search_handle).ok_or(ErrorCode::WalletInvalidHandle)?;
+
+            search.next().ok_or(ErrorCode::WalletItemNotFound)??
+        };
+
+        let record_handle = add_record_entry(record)?;
+
+        unsafe { *record_handle_p = record_handle; }
+        Ok(())
+    })
+}
+
+pub extern "C" fn free_search(_storage_handle: Handle,
+                          search_handle: Handle) -> Error {
+    guard(|| SEARCHES.lock().unwrap()
+        .remove(&search_handle)
+        .map(|_| ())
+        .ok_or(ErrorCode::WalletInvalidHandle))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn guard_returns_error_of_callback() {
+        assert_eq!(guard(|| Ok(())), ErrorCode::Success as Error);
+        assert_eq!(guard(|| Err(ErrorCode::WalletItemNotFound)), ErrorCode::WalletItemNotFound as Error);
+    }
+
+    #[test]
+    fn guard_catches_panic() {
+        assert_eq!(guard(|| panic!("storage failed")), ErrorCode::CommonInvalidState as Error);
+    }
+}
@@ -0,0 +1,317 @@
+use ErrorCode;
+use wallet::Wallet;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use super::{WalletStorage, StorageRecord, StorageSearch, RecordOptions, SearchOptions, Query, Tags};
+
+struct InmemRecord {
+    value: Vec<u8>,
+    tags: Tags,
+}
+
+struct InmemWallet {
+    metadata: String,
+    records: HashMap<(String, String), InmemRecord>,
+    handles: usize,
+}
+
+lazy_static! {
+    static ref WALLETS: Mutex<HashMap<String, Arc<Mutex<InmemWallet>>>> = Default::default();
+}
+
+/// Wallet storage that keeps everything in process memory.
+///
+/// A wallet lives from `Wallet::create` until its last handle is closed or it is deleted
+/// with `Wallet::delete`, whichever comes first. Opening it again after that fails with
+/// `WalletNotFoundError`. Storage config and credentials are ignored.
+pub struct InmemStorage {
+    wallet: Arc<Mutex<InmemWallet>>,
+}
+
+impl InmemStorage {
+    /// Storage type name used by `InmemStorage::register`.
+    pub const TYPE: &'static str = "inmem";
+
+    /// Registers the storage with libindy as `InmemStorage::TYPE`.
+    ///
+    /// Wallets use it when created or opened with `"storage_type": "inmem"` in the config.
+    pub fn register() -> Result<(), ErrorCode> {
+        Wallet::register_storage_impl::<InmemStorage>(InmemStorage::TYPE)
+    }
+
+    fn record(id: &str, type_: &str, record: &InmemRecord, options: &RecordOptions) -> StorageRecord {
+        StorageRecord {
+            id: id.to_string(),
+            type_: if options.retrieve_type { Some(type_.to_string()) } else { None },
+            value: if options.retrieve_value { Some(record.value.clone()) } else { None },
+            tags: if options.retrieve_tags { Some(record.tags.clone()) } else { None },
+        }
+    }
+
+    fn with_record<F>(&mut self, type_: &str, id: &str, f: F) -> Result<(), ErrorCode> where F: FnOnce(&mut InmemRecord) {
+        let mut wallet = self.wallet.lock().unwrap();
+
+        match wallet.records.get_mut(&(type_.to_string(), id.to_string())) {
+            Some(record) => Ok(f(record)),
+            None => Err(ErrorCode::WalletItemNotFound)
+        }
+    }
+}
+
+impl WalletStorage for InmemStorage {
+    fn create(id: &str, _config: Option<&str>, _credentials: Option<&str>, metadata: &str) -> Result<(), ErrorCode> {
+        let mut wallets = WALLETS.lock().unwrap();
+
+        if wallets.contains_key(id) {
+            return Err(ErrorCode::WalletAlreadyExistsError);
+        }
+
+        let wallet = InmemWallet { metadata: metadata.to_string(), records: HashMap::new(), handles: 0 };
+        wallets.insert(id.to_string(), Arc::new(Mutex::new(wallet)));
+
+        Ok(())
+    }
+
+    fn open(id: &str, _config: Option<&str>, _credentials: Option<&str>) -> Result<InmemStorage, ErrorCode> {
+        let wallets = WALLETS.lock().unwrap();
+        let wallet = wallets.get(id).ok_or(ErrorCode::WalletNotFoundError)?;
+
+        wallet.lock().unwrap().handles += 1;
+
+        Ok(InmemStorage { wallet: wallet.clone() })
+    }
+
+    fn delete(id: &str, _config: Option<&str>, _credentials: Option<&str>) -> Result<(), ErrorCode> {
+        WALLETS.lock().unwrap()
+            .remove(id)
+            .map(|_| ())
+            .ok_or(ErrorCode::WalletNotFoundError)
+    }
+
+    fn close(&mut self) -> Result<(), ErrorCode> {
+        let mut wallets = WALLETS.lock().unwrap();
+
+        let handles = {
+            let mut wallet = self.wallet.lock().unwrap();
+            wallet.handles = wallet.handles.saturating_sub(1);
+            wallet.handles
+        };
+
+        if handles == 0 {
+            // The wallet may already be deleted, or deleted and created again under the same id.
+            wallets.retain(|_, wallet| !Arc::ptr_eq(wallet, &self.wallet));
+        }
+
+        Ok(())
+    }
+
+    fn add_record(&mut self, type_: &str, id: &str, value: &[u8], tags: &Tags) -> Result<(), ErrorCode> {
+        let mut wallet = self.wallet.lock().unwrap();
+        let key = (type_.to_string(), id.to_string());
+
+        if wallet.records.contains_key(&key) {
+            return Err(ErrorCode::WalletItemAlreadyExists);
+        }
+
+        wallet.records.insert(key, InmemRecord { value: value.to_vec(), tags: tags.clone() });
+
+        Ok(())
+    }
+
+    fn update_record_value(&mut self, type_: &str, id: &str, value: &[u8]) -> Result<(), ErrorCode> {
+        self.with_record(type_, id, |record| record.value = value.to_vec())
+    }
+
+    fn update_record_tags(&mut self, type_: &str, id: &str, tags: &Tags) -> Result<(), ErrorCode> {
+        self.with_record(type_, id, |record| record.tags = tags.clone())
+    }
+
+    fn add_record_tags(&mut self, type_: &str, id: &str, tags: &Tags) -> Result<(), ErrorCode> {
+        self.with_record(type_, id, |record| record.tags.extend(tags.clone()))
+    }
+
+    fn delete_record_tags(&mut self, type_: &str, id: &str, tag_names: &[String]) -> Result<(), ErrorCode> {
+        self.with_record(type_, id, |record| {
+            for name in tag_names {
+                record.tags.remove(name);
+            }
+        })
+    }
+
+    fn delete_record(&mut self, type_: &str, id: &str) -> Result<(), ErrorCode> {
+        self.wallet.lock().unwrap()
+            .records
+            .remove(&(type_.to_string(), id.to_string()))
+            .map(|_| ())
+            .ok_or(ErrorCode::WalletItemNotFound)
+    }
+
+    fn get_record(&self, type_: &str, id: &str, options: &RecordOptions) -> Result<StorageRecord, ErrorCode> {
+        self.wallet.lock().unwrap()
+            .records
+            .get(&(type_.to_string(), id.to_string()))
+            .map(|record| InmemStorage::record(id, type_, record, options))
+            .ok_or(ErrorCode::WalletItemNotFound)
+    }
+
+    fn get_storage_metadata(&self) -> Result<String, ErrorCode> {
+        Ok(self.wallet.lock().unwrap().metadata.clone())
+    }
+
+    fn set_storage_metadata(&mut self, metadata: &str) -> Result<(), ErrorCode> {
+        self.wallet.lock().unwrap().metadata = metadata.to_string();
+        Ok(())
+    }
+
+    fn search_records(&self, type_: &str, query: &Query, options: &SearchOptions) -> Result<StorageSearch, ErrorCode> {
+        let wallet = self.wallet.lock().unwrap();
+        let record_options = options.record_options();
+
+        let records: Vec<StorageRecord> = wallet.records.iter()
+            .filter(|&(&(ref record_type, _), ref record)| record_type == type_ && query.matches(&record.tags))
+            .map(|(&(ref record_type, ref id), record)| InmemStorage::record(id, record_type, record, &record_options))
+            .collect();
+
+        let total_count = if options.retrieve_total_count { Some(records.len()) } else { None };
+
+        if !options.retrieve_records {
+            return Ok(StorageSearch::empty(total_count));
+        }
+
+        Ok(StorageSearch::new(records.into_iter().map(Ok), total_count))
+    }
+
+    fn search_all_records(&self) -> Result<StorageSearch, ErrorCode> {
+        let wallet = self.wallet.lock().unwrap();
+        let options = RecordOptions { retrieve_type: true, retrieve_value: true, retrieve_tags: true };
+
+        let records: Vec<StorageRecord> = wallet.records.iter()
+            .map(|(&(ref type_, ref id), record)| InmemStorage::record(id, type_, record, &options))
+            .collect();
+
+        let total_count = records.len();
+
+        Ok(StorageSearch::new(records.into_iter().map(Ok), Some(total_count)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use utils::sequence::SequenceUtils;
+
+    fn wallet_id() -> String {
+        format!("inmem_wallet_{}", SequenceUtils::get_next_id())
+    }
+
+    fn tags(pairs: &[(&str, &str)]) -> Tags {
+        pairs.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn create_open_delete_works() {
+        let id = wallet_id();
+
+        assert_eq!(InmemStorage::open(&id, None, None).err(), Some(ErrorCode::WalletNotFoundError));
+
+        InmemStorage::create(&id, None, None, "metadata").unwrap();
+        assert_eq!(InmemStorage::create(&id, None, None, "metadata").unwrap_err(), ErrorCode::WalletAlreadyExistsError);
+
+        let mut storage = InmemStorage::open(&id, None, None).unwrap();
+        storage.add_record("type", "id", b"value", &Tags::new()).unwrap();
+        assert_eq!(storage.get_storage_metadata().unwrap(), "metadata");
+
+        InmemStorage::delete(&id, None, None).unwrap();
+        assert_eq!(InmemStorage::open(&id, None, None).err(), Some(ErrorCode::WalletNotFoundError));
+        assert_eq!(InmemStorage::delete(&id, None, None).unwrap_err(), ErrorCode::WalletNotFoundError);
+        storage.close().unwrap();
+    }
+
+    #[test]
+    fn close_of_last_handle_drops_wallet() {
+        let id = wallet_id();
+        InmemStorage::create(&id, None, None, "metadata").unwrap();
+
+        let mut storage_1 = InmemStorage::open(&id, None, None).unwrap();
+        let mut storage_2 = InmemStorage::open(&id, None, None).unwrap();
+        storage_1.add_record("type", "id", b"value", &Tags::new()).unwrap();
+
+        storage_1.close().unwrap();
+        assert!(storage_2.get_record("type", "id", &RecordOptions::default()).is_ok());
+        assert!(InmemStorage::open(&id, None, None).and_then(|mut storage| storage.close()).is_ok());
+
+        storage_2.close().unwrap();
+        assert_eq!(InmemStorage::open(&id, None, None).err(), Some(ErrorCode::WalletNotFoundError));
+        assert_eq!(InmemStorage::delete(&id, None, None).unwrap_err(), ErrorCode::WalletNotFoundError);
+    }
+
+    #[test]
+    fn close_after_delete_keeps_recreated_wallet() {
+        let id = wallet_id();
+        InmemStorage::create(&id, None, None, "old").unwrap();
+        let mut storage = InmemStorage::open(&id, None, None).unwrap();
+
+        InmemStorage::delete(&id, None, None).unwrap();
+        InmemStorage::create(&id, None, None, "new").unwrap();
+        storage.close().unwrap();
+
+        let mut storage = InmemStorage::open(&id, None, None).unwrap();
+        assert_eq!(storage.get_storage_metadata().unwrap(), "new");
+        storage.close().unwrap();
+    }
+
+    #[test]
+    fn records_work() {
+        let id = wallet_id();
+        InmemStorage::create(&id, None, None, "metadata").unwrap();
+        let mut storage = InmemStorage::open(&id, None, None).unwrap();
+        let options = RecordOptions { retrieve_type: true, retrieve_value: true, retrieve_tags: true };
+
+        storage.add_record("type", "id", b"value", &tags(&[("tag1", "a")])).unwrap();
+        assert_eq!(storage.add_record("type", "id", b"value", &Tags::new()).unwrap_err(), ErrorCode::WalletItemAlreadyExists);
+
+        storage.update_record_value("type", "id", b"value2").unwrap();
+        storage.add_record_tags("type", "id", &tags(&[("~tag2", "b")])).unwrap();
+
+        let record = storage.get_record("type", "id", &options).unwrap();
+        assert_eq!(record, StorageRecord::new("id", Some("type"), Some(b"value2"), Some(&tags(&[("tag1", "a"), ("~tag2", "b")]))));
+
+        storage.delete_record_tags("type", "id", &["tag1".to_string()]).unwrap();
+        storage.update_record_tags("type", "id", &tags(&[("tag3", "c")])).unwrap();
+        assert_eq!(storage.get_record("type", "id", &options).unwrap().tags, Some(tags(&[("tag3", "c")])));
+
+        storage.delete_record("type", "id").unwrap();
+        assert_eq!(storage.get_record("type", "id", &options).unwrap_err(), ErrorCode::WalletItemNotFound);
+        assert_eq!(storage.update_record_tags("type", "id", &Tags::new()).unwrap_err(), ErrorCode::WalletItemNotFound);
+
+        InmemStorage::delete(&id, None, None).unwrap();
+    }
+
+    #[test]
+    fn search_records_works() {
+        let id = wallet_id();
+        InmemStorage::create(&id, None, None, "metadata").unwrap();
+        let mut storage = InmemStorage::open(&id, None, None).unwrap();
+
+        storage.add_record("type", "id1", b"value1", &tags(&[("tag1", "a")])).unwrap();
+        storage.add_record("type", "id2", b"value2", &tags(&[("tag1", "b")])).unwrap();
+        storage.add_record("other", "id3", b"value3", &tags(&[("tag1", "a")])).unwrap();
+
+        let options = SearchOptions { retrieve_total_count: true, ..SearchOptions::default() };
+        let search = storage.search_records("type", &Query::parse(r#"{"tag1": "a"}"#).unwrap(), &options).unwrap();
+        assert_eq!(search.total_count(), Some(1));
+        assert_eq!(search.map(|record| record.unwrap().id).collect::<Vec<String>>(), vec!["id1"]);
+
+        let options = SearchOptions { retrieve_records: false, retrieve_total_count: true, ..SearchOptions::default() };
+        let search = storage.search_records("type", &Query::parse("{}").unwrap(), &options).unwrap();
+        assert_eq!(search.total_count(), Some(2));
+        assert_eq!(search.count(), 0);
+
+        assert_eq!(storage.search_all_records().unwrap().count(), 3);
+
+        InmemStorage::delete(&id, None, None).unwrap();
+    }
+}
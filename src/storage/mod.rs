@@ -0,0 +1,234 @@
+pub mod inmem;
+pub mod query;
+pub(crate) mod callbacks;
+#[cfg(feature = "sqlite_storage")]
+pub mod sqlite;
+
+use ErrorCode;
+
+use serde_json;
+use serde_json::Value;
+
+use std::collections::HashMap;
+
+pub use self::query::Query;
+
+/// Record tags: tag name to tag value. Names of plain (unencrypted) tags start with `~`.
+pub type Tags = HashMap<String, String>;
+
+/// Record as returned by a storage. Parts that were not requested are `None`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageRecord {
+    pub id: String,
+    pub type_: Option<String>,
+    pub value: Option<Vec<u8>>,
+    pub tags: Option<Tags>,
+}
+
+impl StorageRecord {
+    pub fn new(id: &str, type_: Option<&str>, value: Option<&[u8]>, tags: Option<&Tags>) -> StorageRecord {
+        StorageRecord {
+            id: id.to_string(),
+            type_: type_.map(String::from),
+            value: value.map(|value| value.to_vec()),
+            tags: tags.cloned(),
+        }
+    }
+}
+
+/// Parts of a record requested by libindy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RecordOptions {
+    pub retrieve_type: bool,
+    pub retrieve_value: bool,
+    pub retrieve_tags: bool,
+}
+
+impl Default for RecordOptions {
+    fn default() -> RecordOptions {
+        RecordOptions { retrieve_type: false, retrieve_value: true, retrieve_tags: false }
+    }
+}
+
+impl RecordOptions {
+    pub fn from_json(options_json: &str) -> Result<RecordOptions, ErrorCode> {
+        let options = parse_options(options_json)?;
+        let default = RecordOptions::default();
+
+        Ok(RecordOptions {
+            retrieve_type: bool_option(&options, "retrieveType", default.retrieve_type),
+            retrieve_value: bool_option(&options, "retrieveValue", default.retrieve_value),
+            retrieve_tags: bool_option(&options, "retrieveTags", default.retrieve_tags),
+        })
+    }
+}
+
+/// Parts of a search result requested by libindy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchOptions {
+    pub retrieve_records: bool,
+    pub retrieve_total_count: bool,
+    pub retrieve_type: bool,
+    pub retrieve_value: bool,
+    pub retrieve_tags: bool,
+}
+
+impl Default for SearchOptions {
+    fn default() -> SearchOptions {
+        SearchOptions {
+            retrieve_records: true,
+            retrieve_total_count: false,
+            retrieve_type: false,
+            retrieve_value: true,
+            retrieve_tags: false,
+        }
+    }
+}
+
+impl SearchOptions {
+    pub fn from_json(options_json: &str) -> Result<SearchOptions, ErrorCode> {
+        let options = parse_options(options_json)?;
+        let default = SearchOptions::default();
+
+        Ok(SearchOptions {
+            retrieve_records: bool_option(&options, "retrieveRecords", default.retrieve_records),
+            retrieve_total_count: bool_option(&options, "retrieveTotalCount", default.retrieve_total_count),
+            retrieve_type: bool_option(&options, "retrieveType", default.retrieve_type),
+            retrieve_value: bool_option(&options, "retrieveValue", default.retrieve_value),
+            retrieve_tags: bool_option(&options, "retrieveTags", default.retrieve_tags),
+        })
+    }
+
+    /// Options to apply to every record of the search.
+    pub fn record_options(&self) -> RecordOptions {
+        RecordOptions {
+            retrieve_type: self.retrieve_type,
+            retrieve_value: self.retrieve_value,
+            retrieve_tags: self.retrieve_tags,
+        }
+    }
+}
+
+fn parse_options(options_json: &str) -> Result<Value, ErrorCode> {
+    if options_json.is_empty() {
+        return Ok(Value::Object(Default::default()));
+    }
+
+    serde_json::from_str(options_json).map_err(|err| {
+        warn!("Invalid record options - {}", err);
+        ErrorCode::WalletInputError
+    })
+}
+
+fn bool_option(options: &Value, name: &str, default: bool) -> bool {
+    options[name].as_bool().unwrap_or(default)
+}
+
+/// Result of a search. Records are pulled lazily by libindy one at a time.
+pub struct StorageSearch {
+    total_count: Option<usize>,
+    records: Box<Iterator<Item=Result<StorageRecord, ErrorCode>> + Send>,
+}
+
+impl StorageSearch {
+    pub fn new<I>(records: I, total_count: Option<usize>) -> StorageSearch
+        where I: IntoIterator<Item=Result<StorageRecord, ErrorCode>>, I::IntoIter: Send + 'static {
+        StorageSearch { total_count, records: Box::new(records.into_iter()) }
+    }
+
+    /// Search that yields no records.
+    pub fn empty(total_count: Option<usize>) -> StorageSearch {
+        StorageSearch::new(Vec::new(), total_count)
+    }
+
+    pub fn total_count(&self) -> Option<usize> {
+        self.total_count
+    }
+}
+
+impl Iterator for StorageSearch {
+    type Item = Result<StorageRecord, ErrorCode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.records.next()
+    }
+}
+
+/// Custom wallet storage, registered with `Wallet::register_storage_impl::<T>(xtype)`.
+///
+/// The crate generates the extern callbacks libindy expects and keeps the handle tables
+/// and the memory handed out to libindy. libindy encrypts record ids, values and tags before
+/// they reach the storage, so all of them are opaque to the implementation.
+///
+/// `create`, `open` and `delete` receive the `id` from the wallet config together with the
+/// optional `storage_config` and `storage_credentials` json. An opened storage is owned by the
+/// crate until libindy closes it.
+///
+/// Errors are returned to libindy as is, so use the wallet error codes: `WalletItemNotFound`,
+/// `WalletItemAlreadyExists`, `WalletNotFoundError`, `WalletAlreadyExistsError`, `WalletStorageError`.
+pub trait WalletStorage: Send {
+    /// Creates the storage and stores its initial `metadata`.
+    fn create(id: &str, config: Option<&str>, credentials: Option<&str>, metadata: &str) -> Result<(), ErrorCode> where Self: Sized;
+
+    /// Opens a previously created storage.
+    fn open(id: &str, config: Option<&str>, credentials: Option<&str>) -> Result<Self, ErrorCode> where Self: Sized;
+
+    /// Deletes the storage with all its records.
+    fn delete(id: &str, config: Option<&str>, credentials: Option<&str>) -> Result<(), ErrorCode> where Self: Sized;
+
+    /// Called before the storage is dropped.
+    fn close(&mut self) -> Result<(), ErrorCode> {
+        Ok(())
+    }
+
+    fn add_record(&mut self, type_: &str, id: &str, value: &[u8], tags: &Tags) -> Result<(), ErrorCode>;
+
+    fn update_record_value(&mut self, type_: &str, id: &str, value: &[u8]) -> Result<(), ErrorCode>;
+
+    /// Replaces all tags of the record.
+    fn update_record_tags(&mut self, type_: &str, id: &str, tags: &Tags) -> Result<(), ErrorCode>;
+
+    /// Adds new tags to the record and overrides the values of existing ones.
+    fn add_record_tags(&mut self, type_: &str, id: &str, tags: &Tags) -> Result<(), ErrorCode>;
+
+    fn delete_record_tags(&mut self, type_: &str, id: &str, tag_names: &[String]) -> Result<(), ErrorCode>;
+
+    fn delete_record(&mut self, type_: &str, id: &str) -> Result<(), ErrorCode>;
+
+    /// Returns the record with the parts requested in `options`. Fails with `WalletItemNotFound` if there is no such record.
+    fn get_record(&self, type_: &str, id: &str, options: &RecordOptions) -> Result<StorageRecord, ErrorCode>;
+
+    fn get_storage_metadata(&self) -> Result<String, ErrorCode>;
+
+    fn set_storage_metadata(&mut self, metadata: &str) -> Result<(), ErrorCode>;
+
+    /// Searches records of `type_` matching `query`.
+    ///
+    /// The search must not borrow the storage; it may outlive other calls on it.
+    fn search_records(&self, type_: &str, query: &Query, options: &SearchOptions) -> Result<StorageSearch, ErrorCode>;
+
+    /// Returns all records with type, value and tags. Used for wallet export.
+    fn search_all_records(&self) -> Result<StorageSearch, ErrorCode>;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn record_options_from_json_works() {
+        assert_eq!(RecordOptions::from_json("{}").unwrap(), RecordOptions::default());
+        assert_eq!(RecordOptions::from_json(r#"{"retrieveType": true, "retrieveValue": false}"#).unwrap(),
+                   RecordOptions { retrieve_type: true, retrieve_value: false, retrieve_tags: false });
+        assert_eq!(RecordOptions::from_json("not json").unwrap_err(), ErrorCode::WalletInputError);
+    }
+
+    #[test]
+    fn search_options_from_json_works() {
+        let options = SearchOptions::from_json(r#"{"retrieveTotalCount": true, "retrieveTags": true}"#).unwrap();
+
+        assert!(options.retrieve_records);
+        assert!(options.retrieve_total_count);
+        assert_eq!(options.record_options(), RecordOptions { retrieve_type: false, retrieve_value: true, retrieve_tags: true });
+    }
+}
@@ -0,0 +1,203 @@
+use ErrorCode;
+
+use serde_json;
+use serde_json::{Map, Value};
+
+use super::Tags;
+
+/// Parsed wallet query (WQL) as it is passed by libindy to a storage plugin.
+///
+/// Tag names and values are opaque to the storage: libindy encrypts and encodes them
+/// before calling the plugin, so `Eq`, `Neq` and `In` are the only operators libindy
+/// uses for encrypted tags. Names of plain tags start with `~`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+    Eq(String, String),
+    Neq(String, String),
+    Gt(String, String),
+    Gte(String, String),
+    Lt(String, String),
+    Lte(String, String),
+    Like(String, String),
+    In(String, Vec<String>),
+}
+
+impl Query {
+    /// Parses a WQL json. An empty object matches every record.
+    pub fn parse(query_json: &str) -> Result<Query, ErrorCode> {
+        let query: Value = serde_json::from_str(query_json).map_err(|err| {
+            warn!("Invalid wallet query - {}", err);
+            ErrorCode::WalletQueryError
+        })?;
+
+        match query {
+            Value::Object(map) => Query::parse_object(map),
+            _ => Err(ErrorCode::WalletQueryError)
+        }
+    }
+
+    /// Checks whether a record with `tags` satisfies the query.
+    ///
+    /// Every operator except `Not` requires the tag to be present.
+    pub fn matches(&self, tags: &Tags) -> bool {
+        match *self {
+            Query::And(ref queries) => queries.iter().all(|query| query.matches(tags)),
+            Query::Or(ref queries) => queries.iter().any(|query| query.matches(tags)),
+            Query::Not(ref query) => !query.matches(tags),
+            Query::Eq(ref name, ref value) => tags.get(name).map_or(false, |tag| tag == value),
+            Query::Neq(ref name, ref value) => tags.get(name).map_or(false, |tag| tag != value),
+            Query::Gt(ref name, ref value) => tags.get(name).map_or(false, |tag| tag > value),
+            Query::Gte(ref name, ref value) => tags.get(name).map_or(false, |tag| tag >= value),
+            Query::Lt(ref name, ref value) => tags.get(name).map_or(false, |tag| tag < value),
+            Query::Lte(ref name, ref value) => tags.get(name).map_or(false, |tag| tag <= value),
+            Query::Like(ref name, ref pattern) => tags.get(name).map_or(false, |tag| Query::like(tag, pattern)),
+            Query::In(ref name, ref values) => tags.get(name).map_or(false, |tag| values.contains(tag)),
+        }
+    }
+
+    fn parse_object(map: Map<String, Value>) -> Result<Query, ErrorCode> {
+        let mut queries = Vec::with_capacity(map.len());
+
+        for (key, value) in map {
+            queries.push(Query::parse_clause(key, value)?);
+        }
+
+        Ok(if queries.len() == 1 { queries.remove(0) } else { Query::And(queries) })
+    }
+
+    fn parse_clause(key: String, value: Value) -> Result<Query, ErrorCode> {
+        match (key.as_str(), value) {
+            ("$and", Value::Array(values)) => Ok(Query::And(Query::parse_list(values)?)),
+            ("$or", Value::Array(values)) => Ok(Query::Or(Query::parse_list(values)?)),
+            ("$not", Value::Object(map)) => Ok(Query::Not(Box::new(Query::parse_object(map)?))),
+            (name, _) if name.starts_with('$') => Err(ErrorCode::WalletQueryError),
+            (_, Value::String(value)) => Ok(Query::Eq(key, value)),
+            (_, Value::Object(map)) => Query::parse_operator(key, map),
+            _ => Err(ErrorCode::WalletQueryError)
+        }
+    }
+
+    fn parse_list(values: Vec<Value>) -> Result<Vec<Query>, ErrorCode> {
+        values.into_iter()
+            .map(|value| match value {
+                Value::Object(map) => Query::parse_object(map),
+                _ => Err(ErrorCode::WalletQueryError)
+            })
+            .collect()
+    }
+
+    fn parse_operator(name: String, map: Map<String, Value>) -> Result<Query, ErrorCode> {
+        if map.len() != 1 {
+            return Err(ErrorCode::WalletQueryError);
+        }
+
+        let (operator, value) = map.into_iter().next().unwrap();
+
+        match (operator.as_str(), value) {
+            ("$neq", Value::String(value)) => Ok(Query::Neq(name, value)),
+            ("$gt", Value::String(value)) => Ok(Query::Gt(name, value)),
+            ("$gte", Value::String(value)) => Ok(Query::Gte(name, value)),
+            ("$lt", Value::String(value)) => Ok(Query::Lt(name, value)),
+            ("$lte", Value::String(value)) => Ok(Query::Lte(name, value)),
+            ("$like", Value::String(value)) => Ok(Query::Like(name, value)),
+            ("$in", Value::Array(values)) => {
+                let values = values.into_iter()
+                    .map(|value| match value {
+                        Value::String(value) => Ok(value),
+                        _ => Err(ErrorCode::WalletQueryError)
+                    })
+                    .collect::<Result<Vec<String>, ErrorCode>>()?;
+
+                Ok(Query::In(name, values))
+            }
+            _ => Err(ErrorCode::WalletQueryError)
+        }
+    }
+
+    /// SQL `LIKE` matching: `%` matches any sequence of characters and `_` a single character.
+    fn like(value: &str, pattern: &str) -> bool {
+        let value: Vec<char> = value.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+
+        let (mut v, mut p) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while v < value.len() {
+            if p < pattern.len() && (pattern[p] == '_' || pattern[p] == value[v]) {
+                v += 1;
+                p += 1;
+            } else if p < pattern.len() && pattern[p] == '%' {
+                backtrack = Some((p, v));
+                p += 1;
+            } else if let Some((bp, bv)) = backtrack {
+                backtrack = Some((bp, bv + 1));
+                p = bp + 1;
+                v = bv + 1;
+            } else {
+                return false;
+            }
+        }
+
+        pattern[p..].iter().all(|c| *c == '%')
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> Tags {
+        pairs.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn parse_works() {
+        let query = Query::parse(r#"{"tag1": "a", "~tag2": {"$gt": "5"}}"#).unwrap();
+        match query {
+            Query::And(ref queries) => {
+                assert!(queries.contains(&Query::Eq("tag1".to_string(), "a".to_string())));
+                assert!(queries.contains(&Query::Gt("~tag2".to_string(), "5".to_string())));
+            }
+            _ => panic!("Expected And, got {:?}", query)
+        }
+
+        let query = Query::parse(r#"{"$or": [{"tag1": "a"}, {"$not": {"tag2": {"$in": ["b", "c"]}}}]}"#).unwrap();
+        assert_eq!(query, Query::Or(vec![
+            Query::Eq("tag1".to_string(), "a".to_string()),
+            Query::Not(Box::new(Query::In("tag2".to_string(), vec!["b".to_string(), "c".to_string()]))),
+        ]));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_queries() {
+        assert_eq!(Query::parse("[]").unwrap_err(), ErrorCode::WalletQueryError);
+        assert_eq!(Query::parse(r#"{"tag1": 1}"#).unwrap_err(), ErrorCode::WalletQueryError);
+        assert_eq!(Query::parse(r#"{"tag1": {"$regex": "a"}}"#).unwrap_err(), ErrorCode::WalletQueryError);
+        assert_eq!(Query::parse(r#"{"$and": {"tag1": "a"}}"#).unwrap_err(), ErrorCode::WalletQueryError);
+    }
+
+    #[test]
+    fn matches_works() {
+        let record_tags = tags(&[("tag1", "a"), ("~tag2", "5")]);
+
+        assert!(Query::parse("{}").unwrap().matches(&record_tags));
+        assert!(Query::parse(r#"{"tag1": "a", "~tag2": {"$gte": "5"}}"#).unwrap().matches(&record_tags));
+        assert!(!Query::parse(r#"{"tag1": "b"}"#).unwrap().matches(&record_tags));
+        assert!(!Query::parse(r#"{"tag3": {"$neq": "a"}}"#).unwrap().matches(&record_tags));
+        assert!(Query::parse(r#"{"$not": {"tag3": "a"}}"#).unwrap().matches(&record_tags));
+        assert!(Query::parse(r#"{"$or": [{"tag1": "b"}, {"~tag2": {"$in": ["4", "5"]}}]}"#).unwrap().matches(&record_tags));
+    }
+
+    #[test]
+    fn like_works() {
+        assert!(Query::like("value", "val%"));
+        assert!(Query::like("value", "%lu%"));
+        assert!(Query::like("value", "v_lue"));
+        assert!(Query::like("value", "%"));
+        assert!(!Query::like("value", "val"));
+        assert!(!Query::like("value", "%x%"));
+    }
+}
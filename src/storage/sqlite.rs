@@ -0,0 +1,471 @@
+use ErrorCode;
+use wallet::Wallet;
+
+use rusqlite;
+use rusqlite::{Connection, OpenFlags, NO_PARAMS};
+use rusqlite::types::ToSql;
+use serde_json;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use super::{WalletStorage, StorageRecord, StorageSearch, RecordOptions, SearchOptions, Query, Tags};
+
+const DB_FILE: &str = "sqlite.db";
+
+const SCHEMA: &str = "
+    PRAGMA locking_mode=EXCLUSIVE;
+    PRAGMA foreign_keys=ON;
+
+    CREATE TABLE metadata(
+        id INTEGER NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY(id)
+    );
+
+    CREATE TABLE items(
+        id INTEGER NOT NULL,
+        type TEXT NOT NULL,
+        name TEXT NOT NULL,
+        value BLOB NOT NULL,
+        PRIMARY KEY(id)
+    );
+
+    CREATE UNIQUE INDEX ux_items_type_name ON items(type, name);
+
+    CREATE TABLE tags(
+        item_id INTEGER NOT NULL,
+        name TEXT NOT NULL,
+        value TEXT NOT NULL,
+        PRIMARY KEY(name, item_id),
+        FOREIGN KEY(item_id) REFERENCES items(id) ON DELETE CASCADE ON UPDATE CASCADE
+    );
+
+    CREATE INDEX ix_tags_name_value ON tags(name, value);
+    CREATE INDEX ix_tags_item_id ON tags(item_id);
+";
+
+/// Wallet storage that keeps every wallet in its own SQLite file.
+///
+/// The file is `<path>/<id>/sqlite.db` where `path` is taken from the storage config
+/// (`{"path": "/var/lib/wallets"}`) and defaults to `~/.indy_client/wallet`.
+/// Records are kept in the `items` table and tags in the indexed `tags` table, so the
+/// wallets can be inspected with the standard SQLite tools.
+///
+/// Requires the `sqlite_storage` feature.
+pub struct SQLiteStorage {
+    conn: Connection,
+}
+
+impl SQLiteStorage {
+    /// Storage type name used by `SQLiteStorage::register`.
+    pub const TYPE: &'static str = "sqlite";
+
+    /// Registers the storage with libindy as `SQLiteStorage::TYPE`.
+    ///
+    /// Wallets use it when created or opened with `"storage_type": "sqlite"` in the config.
+    pub fn register() -> Result<(), ErrorCode> {
+        Wallet::register_storage_impl::<SQLiteStorage>(SQLiteStorage::TYPE)
+    }
+
+    fn wallet_path(id: &str, config: Option<&str>) -> Result<PathBuf, ErrorCode> {
+        let config: serde_json::Value = match config {
+            Some(config) => serde_json::from_str(config).map_err(|_| ErrorCode::WalletInputError)?,
+            None => serde_json::Value::Null
+        };
+
+        let mut path = match config["path"].as_str() {
+            Some(path) => PathBuf::from(path),
+            None => {
+                let mut path = env::home_dir().ok_or(ErrorCode::WalletStorageError)?;
+                path.push(".indy_client");
+                path.push("wallet");
+                path
+            }
+        };
+
+        path.push(id);
+        Ok(path)
+    }
+
+    fn item_id(&self, type_: &str, id: &str) -> Result<i64, ErrorCode> {
+        self.conn.query_row("SELECT id FROM items WHERE type = ?1 AND name = ?2",
+                            &[type_, id],
+                            |row| row.get(0))
+            .map_err(map_err)
+    }
+
+    fn item_tags(conn: &Connection, item_id: i64) -> Result<Tags, ErrorCode> {
+        let mut stmt = conn.prepare_cached("SELECT name, value FROM tags WHERE item_id = ?1").map_err(map_err)?;
+        let rows = stmt.query_map(&[&item_id], |row| Ok((row.get(0)?, row.get(1)?))).map_err(map_err)?;
+
+        rows.collect::<Result<Tags, _>>().map_err(map_err)
+    }
+
+    fn insert_tags(conn: &Connection, item_id: i64, tags: &Tags) -> Result<(), ErrorCode> {
+        let mut stmt = conn.prepare_cached("INSERT OR REPLACE INTO tags(item_id, name, value) VALUES (?1, ?2, ?3)").map_err(map_err)?;
+
+        for (name, value) in tags {
+            stmt.execute(&[&item_id as &ToSql, name, value]).map_err(map_err)?;
+        }
+
+        Ok(())
+    }
+
+    fn search(&self, type_: Option<&str>, query: &Query, options: &SearchOptions) -> Result<StorageSearch, ErrorCode> {
+        let mut args: Vec<String> = Vec::new();
+        let mut condition = query_to_sql(query, &mut args);
+
+        if let Some(type_) = type_ {
+            condition = format!("i.type = ? AND ({})", condition);
+            args.insert(0, type_.to_string());
+        }
+
+        let total_count = if options.retrieve_total_count {
+            let count: i64 = self.conn.query_row(&format!("SELECT COUNT(*) FROM items i WHERE {}", condition), &args, |row| row.get(0))
+                .map_err(map_err)?;
+            Some(count as usize)
+        } else {
+            None
+        };
+
+        if !options.retrieve_records {
+            return Ok(StorageSearch::empty(total_count));
+        }
+
+        let mut stmt = self.conn.prepare(&format!("SELECT i.id, i.name, i.type, i.value FROM items i WHERE {}", condition)).map_err(map_err)?;
+        let rows = stmt.query_map(&args, |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, Vec<u8>>(3)?)))
+            .map_err(map_err)?;
+
+        let mut records = Vec::new();
+
+        for row in rows {
+            let (item_id, id, item_type, value) = row.map_err(map_err)?;
+
+            let tags = if options.retrieve_tags { Some(SQLiteStorage::item_tags(&self.conn, item_id)?) } else { None };
+
+            records.push(Ok(StorageRecord {
+                id,
+                type_: if options.retrieve_type { Some(item_type) } else { None },
+                value: if options.retrieve_value { Some(value) } else { None },
+                tags,
+            }));
+        }
+
+        Ok(StorageSearch::new(records, total_count))
+    }
+}
+
+impl WalletStorage for SQLiteStorage {
+    fn create(id: &str, config: Option<&str>, _credentials: Option<&str>, metadata: &str) -> Result<(), ErrorCode> {
+        let path = SQLiteStorage::wallet_path(id, config)?;
+        let db_path = path.join(DB_FILE);
+
+        if db_path.exists() {
+            return Err(ErrorCode::WalletAlreadyExistsError);
+        }
+
+        fs::create_dir_all(&path).map_err(|_| ErrorCode::WalletStorageError)?;
+
+        let conn = Connection::open(&db_path).map_err(map_err)?;
+
+        conn.execute_batch(SCHEMA)
+            .and_then(|_| conn.execute("INSERT INTO metadata(value) VALUES (?1)", &[metadata]))
+            .map(|_| ())
+            .map_err(|err| {
+                let _ = fs::remove_file(&db_path);
+                map_err(err)
+            })
+    }
+
+    fn open(id: &str, config: Option<&str>, _credentials: Option<&str>) -> Result<SQLiteStorage, ErrorCode> {
+        let db_path = SQLiteStorage::wallet_path(id, config)?.join(DB_FILE);
+
+        if !db_path.exists() {
+            return Err(ErrorCode::WalletNotFoundError);
+        }
+
+        let conn = Connection::open_with_flags(&db_path, OpenFlags::SQLITE_OPEN_READ_WRITE).map_err(map_err)?;
+        conn.execute_batch("PRAGMA locking_mode=EXCLUSIVE; PRAGMA foreign_keys=ON;").map_err(map_err)?;
+
+        Ok(SQLiteStorage { conn })
+    }
+
+    fn delete(id: &str, config: Option<&str>, _credentials: Option<&str>) -> Result<(), ErrorCode> {
+        let path = SQLiteStorage::wallet_path(id, config)?;
+
+        if !path.join(DB_FILE).exists() {
+            return Err(ErrorCode::WalletNotFoundError);
+        }
+
+        fs::remove_dir_all(&path).map_err(|_| ErrorCode::WalletStorageError)
+    }
+
+    fn add_record(&mut self, type_: &str, id: &str, value: &[u8], tags: &Tags) -> Result<(), ErrorCode> {
+        let tx = self.conn.transaction().map_err(map_err)?;
+
+        tx.execute("INSERT INTO items(type, name, value) VALUES (?1, ?2, ?3)", &[&type_ as &ToSql, &id, &value])
+            .map_err(map_err)?;
+
+        let item_id = tx.last_insert_rowid();
+        SQLiteStorage::insert_tags(&tx, item_id, tags)?;
+
+        tx.commit().map_err(map_err)
+    }
+
+    fn update_record_value(&mut self, type_: &str, id: &str, value: &[u8]) -> Result<(), ErrorCode> {
+        let updated = self.conn.execute("UPDATE items SET value = ?1 WHERE type = ?2 AND name = ?3", &[&value as &ToSql, &type_, &id])
+            .map_err(map_err)?;
+
+        if updated == 0 { Err(ErrorCode::WalletItemNotFound) } else { Ok(()) }
+    }
+
+    fn update_record_tags(&mut self, type_: &str, id: &str, tags: &Tags) -> Result<(), ErrorCode> {
+        let item_id = self.item_id(type_, id)?;
+        let tx = self.conn.transaction().map_err(map_err)?;
+
+        tx.execute("DELETE FROM tags WHERE item_id = ?1", &[&item_id]).map_err(map_err)?;
+        SQLiteStorage::insert_tags(&tx, item_id, tags)?;
+
+        tx.commit().map_err(map_err)
+    }
+
+    fn add_record_tags(&mut self, type_: &str, id: &str, tags: &Tags) -> Result<(), ErrorCode> {
+        let item_id = self.item_id(type_, id)?;
+        let tx = self.conn.transaction().map_err(map_err)?;
+
+        SQLiteStorage::insert_tags(&tx, item_id, tags)?;
+
+        tx.commit().map_err(map_err)
+    }
+
+    fn delete_record_tags(&mut self, type_: &str, id: &str, tag_names: &[String]) -> Result<(), ErrorCode> {
+        let item_id = self.item_id(type_, id)?;
+        let tx = self.conn.transaction().map_err(map_err)?;
+
+        {
+            let mut stmt = tx.prepare_cached("DELETE FROM tags WHERE item_id = ?1 AND name = ?2").map_err(map_err)?;
+
+            for name in tag_names {
+                stmt.execute(&[&item_id as &ToSql, name]).map_err(map_err)?;
+            }
+        }
+
+        tx.commit().map_err(map_err)
+    }
+
+    fn delete_record(&mut self, type_: &str, id: &str) -> Result<(), ErrorCode> {
+        let deleted = self.conn.execute("DELETE FROM items WHERE type = ?1 AND name = ?2", &[type_, id]).map_err(map_err)?;
+
+        if deleted == 0 { Err(ErrorCode::WalletItemNotFound) } else { Ok(()) }
+    }
+
+    fn get_record(&self, type_: &str, id: &str, options: &RecordOptions) -> Result<StorageRecord, ErrorCode> {
+        let (item_id, value): (i64, Vec<u8>) = self.conn.query_row("SELECT id, value FROM items WHERE type = ?1 AND name = ?2",
+                                                                   &[type_, id],
+                                                                   |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(map_err)?;
+
+        let tags = if options.retrieve_tags { Some(SQLiteStorage::item_tags(&self.conn, item_id)?) } else { None };
+
+        Ok(StorageRecord {
+            id: id.to_string(),
+            type_: if options.retrieve_type { Some(type_.to_string()) } else { None },
+            value: if options.retrieve_value { Some(value) } else { None },
+            tags,
+        })
+    }
+
+    fn get_storage_metadata(&self) -> Result<String, ErrorCode> {
+        self.conn.query_row("SELECT value FROM metadata", NO_PARAMS, |row| row.get(0)).map_err(map_err)
+    }
+
+    fn set_storage_metadata(&mut self, metadata: &str) -> Result<(), ErrorCode> {
+        self.conn.execute("UPDATE metadata SET value = ?1", &[metadata]).map(|_| ()).map_err(map_err)
+    }
+
+    fn search_records(&self, type_: &str, query: &Query, options: &SearchOptions) -> Result<StorageSearch, ErrorCode> {
+        self.search(Some(type_), query, options)
+    }
+
+    fn search_all_records(&self) -> Result<StorageSearch, ErrorCode> {
+        let options = SearchOptions {
+            retrieve_records: true,
+            retrieve_total_count: false,
+            retrieve_type: true,
+            retrieve_value: true,
+            retrieve_tags: true,
+        };
+
+        self.search(None, &Query::And(Vec::new()), &options)
+    }
+}
+
+/// Translates a query into an SQL condition on `items i`, pushing the bound values to `args`.
+fn query_to_sql(query: &Query, args: &mut Vec<String>) -> String {
+    match *query {
+        Query::And(ref queries) if queries.is_empty() => "1".to_string(),
+        Query::Or(ref queries) if queries.is_empty() => "0".to_string(),
+        Query::And(ref queries) => join_to_sql(queries, " AND ", args),
+        Query::Or(ref queries) => join_to_sql(queries, " OR ", args),
+        Query::Not(ref query) => format!("NOT ({})", query_to_sql(query, args)),
+        Query::Eq(ref name, ref value) => tag_to_sql(name, "=", value, args),
+        Query::Neq(ref name, ref value) => tag_to_sql(name, "!=", value, args),
+        Query::Gt(ref name, ref value) => tag_to_sql(name, ">", value, args),
+        Query::Gte(ref name, ref value) => tag_to_sql(name, ">=", value, args),
+        Query::Lt(ref name, ref value) => tag_to_sql(name, "<", value, args),
+        Query::Lte(ref name, ref value) => tag_to_sql(name, "<=", value, args),
+        Query::Like(ref name, ref value) => tag_to_sql(name, "LIKE", value, args),
+        Query::In(ref name, ref values) => {
+            args.push(name.clone());
+            args.extend(values.iter().cloned());
+
+            let placeholders = vec!["?"; values.len()].join(", ");
+            format!("i.id IN (SELECT item_id FROM tags WHERE name = ? AND value IN ({}))", placeholders)
+        }
+    }
+}
+
+fn join_to_sql(queries: &[Query], separator: &str, args: &mut Vec<String>) -> String {
+    let conditions: Vec<String> = queries.iter()
+        .map(|query| format!("({})", query_to_sql(query, args)))
+        .collect();
+
+    conditions.join(separator)
+}
+
+fn tag_to_sql(name: &str, operator: &str, value: &str, args: &mut Vec<String>) -> String {
+    args.push(name.to_string());
+    args.push(value.to_string());
+
+    format!("i.id IN (SELECT item_id FROM tags WHERE name = ? AND value {} ?)", operator)
+}
+
+fn map_err(err: rusqlite::Error) -> ErrorCode {
+    match err {
+        rusqlite::Error::QueryReturnedNoRows => ErrorCode::WalletItemNotFound,
+        rusqlite::Error::SqliteFailure(ref err, _) if err.code == rusqlite::ErrorCode::ConstraintViolation => ErrorCode::WalletItemAlreadyExists,
+        err => {
+            warn!("SQLite storage error - {}", err);
+            ErrorCode::WalletStorageError
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use utils::sequence::SequenceUtils;
+
+    fn config() -> String {
+        let path = env::temp_dir().join("indy_sqlite_storage_test");
+        format!(r#"{{"path": "{}"}}"#, path.to_str().unwrap())
+    }
+
+    fn wallet_id() -> String {
+        format!("wallet_{}_{}", ::std::process::id(), SequenceUtils::get_next_id())
+    }
+
+    fn tags(pairs: &[(&str, &str)]) -> Tags {
+        pairs.iter().map(|&(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn with_storage<F>(f: F) where F: FnOnce(&mut SQLiteStorage) {
+        let id = wallet_id();
+        let config = config();
+
+        SQLiteStorage::create(&id, Some(&config), None, "metadata").unwrap();
+        {
+            let mut storage = SQLiteStorage::open(&id, Some(&config), None).unwrap();
+            f(&mut storage);
+        }
+        SQLiteStorage::delete(&id, Some(&config), None).unwrap();
+    }
+
+    #[test]
+    fn create_open_delete_works() {
+        let id = wallet_id();
+        let config = config();
+
+        assert_eq!(SQLiteStorage::open(&id, Some(&config), None).err(), Some(ErrorCode::WalletNotFoundError));
+
+        SQLiteStorage::create(&id, Some(&config), None, "metadata").unwrap();
+        assert_eq!(SQLiteStorage::create(&id, Some(&config), None, "metadata").unwrap_err(), ErrorCode::WalletAlreadyExistsError);
+
+        {
+            let storage = SQLiteStorage::open(&id, Some(&config), None).unwrap();
+            assert_eq!(storage.get_storage_metadata().unwrap(), "metadata");
+        }
+
+        SQLiteStorage::delete(&id, Some(&config), None).unwrap();
+        assert_eq!(SQLiteStorage::delete(&id, Some(&config), None).unwrap_err(), ErrorCode::WalletNotFoundError);
+    }
+
+    #[test]
+    fn storage_metadata_works() {
+        with_storage(|storage| {
+            storage.set_storage_metadata("new_metadata").unwrap();
+            assert_eq!(storage.get_storage_metadata().unwrap(), "new_metadata");
+        });
+    }
+
+    #[test]
+    fn records_work() {
+        with_storage(|storage| {
+            let options = RecordOptions { retrieve_type: true, retrieve_value: true, retrieve_tags: true };
+
+            storage.add_record("type", "id", b"value", &tags(&[("tag1", "a")])).unwrap();
+            assert_eq!(storage.add_record("type", "id", b"value", &Tags::new()).unwrap_err(), ErrorCode::WalletItemAlreadyExists);
+
+            storage.update_record_value("type", "id", b"value2").unwrap();
+            storage.add_record_tags("type", "id", &tags(&[("~tag2", "b")])).unwrap();
+
+            let record = storage.get_record("type", "id", &options).unwrap();
+            assert_eq!(record, StorageRecord::new("id", Some("type"), Some(b"value2"), Some(&tags(&[("tag1", "a"), ("~tag2", "b")]))));
+
+            storage.delete_record_tags("type", "id", &["tag1".to_string()]).unwrap();
+            storage.update_record_tags("type", "id", &tags(&[("tag3", "c")])).unwrap();
+            assert_eq!(storage.get_record("type", "id", &options).unwrap().tags, Some(tags(&[("tag3", "c")])));
+
+            storage.delete_record("type", "id").unwrap();
+            assert_eq!(storage.get_record("type", "id", &options).unwrap_err(), ErrorCode::WalletItemNotFound);
+            assert_eq!(storage.delete_record("type", "id").unwrap_err(), ErrorCode::WalletItemNotFound);
+            assert_eq!(storage.update_record_value("type", "id", b"value").unwrap_err(), ErrorCode::WalletItemNotFound);
+            assert_eq!(storage.add_record_tags("type", "id", &Tags::new()).unwrap_err(), ErrorCode::WalletItemNotFound);
+        });
+    }
+
+    #[test]
+    fn search_records_works() {
+        with_storage(|storage| {
+            storage.add_record("type", "id1", b"value1", &tags(&[("tag1", "a"), ("~tag2", "1")])).unwrap();
+            storage.add_record("type", "id2", b"value2", &tags(&[("tag1", "b"), ("~tag2", "2")])).unwrap();
+            storage.add_record("type", "id3", b"value3", &Tags::new()).unwrap();
+            storage.add_record("other", "id4", b"value4", &tags(&[("tag1", "a")])).unwrap();
+
+            let options = SearchOptions { retrieve_total_count: true, ..SearchOptions::default() };
+
+            let ids = |query: &str| {
+                let search = storage.search_records("type", &Query::parse(query).unwrap(), &options).unwrap();
+                let total_count = search.total_count();
+                let mut ids: Vec<String> = search.map(|record| record.unwrap().id).collect();
+                ids.sort();
+                assert_eq!(total_count, Some(ids.len()));
+                ids
+            };
+
+            assert_eq!(ids("{}"), vec!["id1", "id2", "id3"]);
+            assert_eq!(ids(r#"{"tag1": "a"}"#), vec!["id1"]);
+            assert_eq!(ids(r#"{"~tag2": {"$gt": "1"}}"#), vec!["id2"]);
+            assert_eq!(ids(r#"{"tag1": {"$in": ["a", "b"]}, "~tag2": {"$like": "%2"}}"#), vec!["id2"]);
+            assert_eq!(ids(r#"{"$not": {"tag1": "a"}}"#), vec!["id2", "id3"]);
+            assert_eq!(ids(r#"{"$or": [{"tag1": "b"}, {"~tag2": "1"}]}"#), vec!["id1", "id2"]);
+
+            let all: Vec<StorageRecord> = storage.search_all_records().unwrap().map(Result::unwrap).collect();
+            assert_eq!(all.len(), 4);
+            assert!(all.contains(&StorageRecord::new("id4", Some("other"), Some(b"value4"), Some(&tags(&[("tag1", "a")])))));
+        });
+    }
+}
@@ -0,0 +1,58 @@
+use num_bigint::BigUint;
+
+const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Base58 (bitcoin alphabet) as used for DIDs and verkeys.
+pub(crate) struct Base58 {}
+
+impl Base58 {
+    pub fn encode(bytes: &[u8]) -> String {
+        let zeros = bytes.iter().take_while(|&&byte| byte == 0).count();
+
+        let mut encoded = "1".repeat(zeros);
+
+        if zeros < bytes.len() {
+            let digits = BigUint::from_bytes_be(&bytes[zeros..]).to_radix_be(58);
+            encoded.extend(digits.iter().map(|&digit| ALPHABET[digit as usize] as char));
+        }
+
+        encoded
+    }
+
+    /// Returns `None` if `encoded` has a character outside of the alphabet.
+    pub fn decode(encoded: &str) -> Option<Vec<u8>> {
+        let digits = encoded.bytes()
+            .map(|c| ALPHABET.iter().position(|&a| a == c).map(|digit| digit as u8))
+            .collect::<Option<Vec<u8>>>()?;
+
+        let zeros = digits.iter().take_while(|&&digit| digit == 0).count();
+
+        let mut decoded = vec![0; zeros];
+
+        if zeros < digits.len() {
+            decoded.extend(BigUint::from_radix_be(&digits[zeros..], 58)?.to_bytes_be());
+        }
+
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_works() {
+        assert_eq!(Base58::encode(b""), "");
+        assert_eq!(Base58::encode(&[0, 0, 1]), "112");
+        assert_eq!(Base58::encode(b"hello world"), "StV1DL6CwTryKyV");
+    }
+
+    #[test]
+    fn decode_works() {
+        assert_eq!(Base58::decode("").unwrap(), b"");
+        assert_eq!(Base58::decode("112").unwrap(), vec![0, 0, 1]);
+        assert_eq!(Base58::decode("StV1DL6CwTryKyV").unwrap(), b"hello world");
+        assert!(Base58::decode("0OIl").is_none());
+    }
+}
@@ -0,0 +1,79 @@
+const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PAD: u8 = b'=';
+
+/// Base64 (standard alphabet, padded) for binary data in json messages.
+pub(crate) struct Base64 {}
+
+impl Base64 {
+    pub fn encode(bytes: &[u8]) -> String {
+        let mut encoded = String::new();
+
+        for chunk in bytes.chunks(3) {
+            let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+            let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 0x3f) as usize] as char);
+                } else {
+                    encoded.push(PAD as char);
+                }
+            }
+        }
+
+        encoded
+    }
+
+    /// Returns `None` if `encoded` is not padded base64.
+    pub fn decode(encoded: &str) -> Option<Vec<u8>> {
+        let encoded = encoded.as_bytes();
+        let mut decoded = Vec::new();
+
+        for (index, chunk) in encoded.chunks(4).enumerate() {
+            let last = (index + 1) * 4 >= encoded.len();
+            let pads = chunk.iter().rev().take_while(|&&c| c == PAD).count();
+
+            if chunk.len() != 4 || pads > 2 || (pads > 0 && !last) {
+                return None;
+            }
+
+            let mut n: u32 = 0;
+            for &c in &chunk[..4 - pads] {
+                let digit = ALPHABET.iter().position(|&a| a == c)?;
+                n = (n << 6) | digit as u32;
+            }
+            n <<= 6 * pads as u32;
+
+            decoded.extend([(n >> 16) as u8, (n >> 8) as u8, n as u8].iter().take(3 - pads));
+        }
+
+        Some(decoded)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn encode_works() {
+        assert_eq!(Base64::encode(b""), "");
+        assert_eq!(Base64::encode(b"f"), "Zg==");
+        assert_eq!(Base64::encode(b"fo"), "Zm8=");
+        assert_eq!(Base64::encode(b"foo"), "Zm9v");
+        assert_eq!(Base64::encode(&[0, 255, 254, 253]), "AP/+/Q==");
+    }
+
+    #[test]
+    fn decode_works() {
+        assert_eq!(Base64::decode("").unwrap(), b"");
+        assert_eq!(Base64::decode("Zg==").unwrap(), b"f");
+        assert_eq!(Base64::decode("Zm8=").unwrap(), b"fo");
+        assert_eq!(Base64::decode("Zm9v").unwrap(), b"foo");
+        assert_eq!(Base64::decode("AP/+/Q==").unwrap(), vec![0, 255, 254, 253]);
+        assert!(Base64::decode("Zm9").is_none());
+        assert!(Base64::decode("Zg==Zm9v").is_none());
+        assert!(Base64::decode("Z===").is_none());
+        assert!(Base64::decode("Zm9*").is_none());
+    }
+}
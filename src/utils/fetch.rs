@@ -0,0 +1,164 @@
+use {ErrorCode, IndyHandle};
+
+use std::thread;
+
+use serde_json;
+use serde_json::Value;
+
+use issuer_setup::ReadRetries;
+use ledger::Ledger;
+use utils::reply::ReplyHandler;
+
+/// NYM of a DID as returned by GET_NYM.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct NymData {
+    pub dest: String,
+    pub role: Option<String>,
+    pub verkey: Option<String>,
+    #[serde(rename = "seqNo", default)]
+    pub seq_no: Option<u64>,
+    #[serde(rename = "txnTime", default)]
+    pub txn_time: Option<u64>,
+}
+
+/// Reads ledger data for the workflows.
+pub(crate) struct Fetch {}
+
+impl Fetch {
+    pub fn schema(pool_handle: IndyHandle, submitter_did: Option<&str>, schema_id: &str) -> Result<String, ErrorCode> {
+        let request = Ledger::build_get_schema_request(submitter_did, schema_id)?;
+        let response = Fetch::submit(pool_handle, &request)?;
+
+        Ledger::parse_get_schema_response(&response).map(|(_, schema_json)| schema_json)
+    }
+
+    pub fn cred_def(pool_handle: IndyHandle, submitter_did: Option<&str>, cred_def_id: &str) -> Result<String, ErrorCode> {
+        let request = Ledger::build_get_cred_def_request(submitter_did, cred_def_id)?;
+        let response = Fetch::submit(pool_handle, &request)?;
+
+        Ledger::parse_get_cred_def_response(&response).map(|(_, cred_def_json)| cred_def_json)
+    }
+
+    pub fn rev_reg_def(pool_handle: IndyHandle, submitter_did: Option<&str>, rev_reg_def_id: &str) -> Result<String, ErrorCode> {
+        let request = Ledger::build_get_revoc_reg_def_request(submitter_did, rev_reg_def_id)?;
+        let response = Fetch::submit(pool_handle, &request)?;
+
+        Ledger::parse_get_revoc_reg_def_response(&response).map(|(_, rev_reg_def_json)| rev_reg_def_json)
+    }
+
+    /// Returns the registry accumulated at `timestamp` and the timestamp of the entry.
+    pub fn rev_reg(pool_handle: IndyHandle, submitter_did: Option<&str>, rev_reg_def_id: &str, timestamp: i64) -> Result<(String, u64), ErrorCode> {
+        let request = Ledger::build_get_revoc_reg_request(submitter_did, rev_reg_def_id, timestamp)?;
+        let response = Fetch::submit(pool_handle, &request)?;
+
+        Ledger::parse_get_revoc_reg_response(&response).map(|(_, rev_reg_json, timestamp)| (rev_reg_json, timestamp))
+    }
+
+    /// Returns the delta and its timestamp. `from` is -1 for a delta from the creation of the registry.
+    pub fn rev_reg_delta(pool_handle: IndyHandle, submitter_did: Option<&str>, rev_reg_def_id: &str, from: i64, to: i64) -> Result<(String, u64), ErrorCode> {
+        let request = Ledger::build_get_revoc_reg_delta_request(submitter_did, rev_reg_def_id, from, to)?;
+        let response = Fetch::submit(pool_handle, &request)?;
+
+        Ledger::parse_get_revoc_reg_delta_response(&response).map(|(_, rev_reg_delta_json, timestamp)| (rev_reg_delta_json, timestamp))
+    }
+
+    /// Returns the NYM of a DID, `None` if it is not on the ledger.
+    pub fn nym(pool_handle: IndyHandle, submitter_did: Option<&str>, did: &str) -> Result<Option<NymData>, ErrorCode> {
+        let request = Ledger::build_get_nym_request(submitter_did, did)?;
+
+        match Fetch::data(pool_handle, &request)? {
+            Some(data) => serde_json::from_str(&data).map(Some).map_err(|_| ErrorCode::CommonInvalidStructure),
+            None => Ok(None)
+        }
+    }
+
+    /// Returns the value of the raw ATTRIB `name` of a DID, `None` if it is not on the ledger.
+    pub fn attrib(pool_handle: IndyHandle, submitter_did: Option<&str>, did: &str, name: &str) -> Result<Option<Value>, ErrorCode> {
+        let request = Ledger::build_get_attrib_request(submitter_did, did, Some(name), None, None)?;
+
+        match Fetch::data(pool_handle, &request)? {
+            Some(data) => {
+                let mut data: Value = serde_json::from_str(&data).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+                Ok(Some(data[name].take()).filter(|value| !value.is_null()))
+            }
+            None => Ok(None)
+        }
+    }
+
+    /// Calls `read` until it returns a value, at least once even if `retries.attempts` is 0.
+    ///
+    /// Used to read back written transactions. Returns the first error of `read` and
+    /// `PoolLedgerTimeout` if no read returned a value.
+    pub fn read_until<F, T>(retries: ReadRetries, mut read: F) -> Result<T, ErrorCode> where F: FnMut() -> Result<Option<T>, ErrorCode> {
+        let attempts = retries.attempts.max(1);
+
+        for attempt in 1..=attempts {
+            if let Some(value) = read()? {
+                return Ok(value);
+            }
+
+            debug!("Transaction is not visible on the ledger yet, attempt {} of {}", attempt, attempts);
+
+            if attempt < attempts {
+                thread::sleep(retries.delay);
+            }
+        }
+
+        Err(ErrorCode::PoolLedgerTimeout)
+    }
+
+    /// Submits a read request and returns the data json of the reply, `None` if it is null.
+    fn data(pool_handle: IndyHandle, request: &str) -> Result<Option<String>, ErrorCode> {
+        let response = Ledger::submit_request(pool_handle, request)?;
+        let reply = ReplyHandler::check(&response)?;
+
+        match reply["result"]["data"] {
+            Value::String(ref data) => Ok(Some(data.clone())),
+            Value::Null => Ok(None),
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+
+    fn submit(pool_handle: IndyHandle, request: &str) -> Result<String, ErrorCode> {
+        let response = Ledger::submit_request(pool_handle, request)?;
+        ReplyHandler::check(&response)?;
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn read_until_reads_at_least_once() {
+        let mut reads = 0;
+        let retries = ReadRetries { attempts: 0, delay: Duration::from_millis(0) };
+
+        let result = Fetch::read_until(retries, || { reads += 1; Ok(Some(reads)) });
+
+        assert_eq!(result, Ok(1));
+        assert_eq!(reads, 1);
+    }
+
+    #[test]
+    fn read_until_gives_up_after_attempts() {
+        let mut reads = 0;
+        let retries = ReadRetries { attempts: 3, delay: Duration::from_millis(0) };
+
+        let result: Result<(), ErrorCode> = Fetch::read_until(retries, || { reads += 1; Ok(None) });
+
+        assert_eq!(result, Err(ErrorCode::PoolLedgerTimeout));
+        assert_eq!(reads, 3);
+
+        reads = 0;
+        let retries = ReadRetries { attempts: 0, delay: Duration::from_millis(0) };
+        let result: Result<(), ErrorCode> = Fetch::read_until(retries, || { reads += 1; Ok(None) });
+
+        assert_eq!(result, Err(ErrorCode::PoolLedgerTimeout));
+        assert_eq!(reads, 1);
+    }
+}
@@ -1,3 +1,8 @@
 pub mod results;
 pub mod callbacks;
-mod sequence;
+pub(crate) mod base58;
+pub(crate) mod base64;
+pub(crate) mod fetch;
+pub mod reply;
+pub(crate) mod records;
+pub(crate) mod sequence;
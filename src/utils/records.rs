@@ -0,0 +1,106 @@
+use {ErrorCode, IndyHandle};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json;
+
+use storage::Tags;
+use wallet::Wallet;
+
+const FETCH_COUNT: usize = 100;
+
+/// Non-secret wallet record with its value deserialized from json.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Record<T> {
+    pub id: String,
+    pub value: T,
+    pub tags: Tags,
+}
+
+#[derive(Deserialize)]
+struct WalletRecord {
+    id: String,
+    value: Option<String>,
+    tags: Option<Tags>,
+}
+
+#[derive(Deserialize)]
+struct WalletSearch {
+    records: Option<Vec<WalletRecord>>,
+}
+
+/// Helpers for workflows that keep their state in non-secret wallet records as json.
+pub(crate) struct Records {}
+
+impl Records {
+    pub fn add<T: Serialize>(wallet_handle: IndyHandle, xtype: &str, id: &str, value: &T, tags: &Tags) -> Result<(), ErrorCode> {
+        Wallet::add_record(wallet_handle, xtype, id, &Records::to_json(value)?, Some(&Records::to_json(tags)?))
+    }
+
+    pub fn update<T: Serialize>(wallet_handle: IndyHandle, xtype: &str, id: &str, value: &T) -> Result<(), ErrorCode> {
+        Wallet::update_record_value(wallet_handle, xtype, id, &Records::to_json(value)?)
+    }
+
+    pub fn update_tags(wallet_handle: IndyHandle, xtype: &str, id: &str, tags: &Tags) -> Result<(), ErrorCode> {
+        Wallet::update_record_tags(wallet_handle, xtype, id, &Records::to_json(tags)?)
+    }
+
+    /// Returns `None` if there is no such record.
+    pub fn get<T: DeserializeOwned>(wallet_handle: IndyHandle, xtype: &str, id: &str) -> Result<Option<Record<T>>, ErrorCode> {
+        let options = json!({"retrieveType": false, "retrieveValue": true, "retrieveTags": true}).to_string();
+
+        match Wallet::get_record(wallet_handle, xtype, id, &options) {
+            Ok(record) => Records::parse(serde_json::from_str(&record).map_err(|_| ErrorCode::CommonInvalidState)?).map(Some),
+            Err(ErrorCode::WalletItemNotFound) => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+
+    pub fn delete(wallet_handle: IndyHandle, xtype: &str, id: &str) -> Result<(), ErrorCode> {
+        Wallet::delete_record(wallet_handle, xtype, id)
+    }
+
+    /// Returns all records of `xtype` that match the wallet query `query_json`.
+    pub fn search<T: DeserializeOwned>(wallet_handle: IndyHandle, xtype: &str, query_json: &str) -> Result<Vec<Record<T>>, ErrorCode> {
+        let options = json!({"retrieveRecords": true, "retrieveTotalCount": false, "retrieveType": false, "retrieveValue": true, "retrieveTags": true}).to_string();
+        let search_handle = Wallet::open_search(wallet_handle, xtype, query_json, &options)?;
+
+        let result = Records::fetch_all(wallet_handle, search_handle);
+        Wallet::close_search(search_handle)?;
+
+        result
+    }
+
+    fn fetch_all<T: DeserializeOwned>(wallet_handle: IndyHandle, search_handle: IndyHandle) -> Result<Vec<Record<T>>, ErrorCode> {
+        let mut records = Vec::new();
+
+        loop {
+            let search = Wallet::fetch_search_next_records(wallet_handle, search_handle, FETCH_COUNT)?;
+            let search: WalletSearch = serde_json::from_str(&search).map_err(|_| ErrorCode::CommonInvalidState)?;
+
+            let fetched = search.records.unwrap_or_default();
+
+            if fetched.is_empty() {
+                return Ok(records);
+            }
+
+            for record in fetched {
+                records.push(Records::parse(record)?);
+            }
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(record: WalletRecord) -> Result<Record<T>, ErrorCode> {
+        let value = record.value.ok_or(ErrorCode::CommonInvalidState)?;
+
+        Ok(Record {
+            id: record.id,
+            value: serde_json::from_str(&value).map_err(|_| ErrorCode::CommonInvalidState)?,
+            tags: record.tags.unwrap_or_default(),
+        })
+    }
+
+    fn to_json<T: Serialize>(value: &T) -> Result<String, ErrorCode> {
+        serde_json::to_string(value).map_err(|_| ErrorCode::CommonInvalidState)
+    }
+}
@@ -0,0 +1,49 @@
+use ErrorCode;
+
+use serde_json;
+use serde_json::Value;
+
+pub struct ReplyHandler {}
+
+impl ReplyHandler {
+    /// Checks that a reply returned by Ledger::submit_request or Ledger::sign_and_submit_request
+    /// was accepted by the pool.
+    ///
+    /// # Returns
+    /// The parsed reply. `REQNACK` and `REJECT` replies are returned as `LedgerInvalidTransaction`.
+    pub fn check(reply: &str) -> Result<Value, ErrorCode> {
+        let reply: Value = serde_json::from_str(reply).map_err(|_| ErrorCode::CommonInvalidStructure)?;
+
+        match reply["op"].as_str() {
+            Some("REPLY") => Ok(reply),
+            Some("REQNACK") | Some("REJECT") => {
+                warn!("Ledger rejected the request - {}", reply["reason"]);
+                Err(ErrorCode::LedgerInvalidTransaction)
+            },
+            _ => Err(ErrorCode::CommonInvalidStructure)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn check_accepts_reply() {
+        let reply = ReplyHandler::check(r#"{"op":"REPLY","result":{"seqNo":10}}"#).unwrap();
+        assert_eq!(reply["result"]["seqNo"], 10);
+    }
+
+    #[test]
+    fn check_rejects_reqnack_and_reject() {
+        assert_eq!(ReplyHandler::check(r#"{"op":"REQNACK","reason":"invalid"}"#).unwrap_err(), ErrorCode::LedgerInvalidTransaction);
+        assert_eq!(ReplyHandler::check(r#"{"op":"REJECT","reason":"unauthorized"}"#).unwrap_err(), ErrorCode::LedgerInvalidTransaction);
+    }
+
+    #[test]
+    fn check_fails_for_malformed_reply() {
+        assert_eq!(ReplyHandler::check("not json").unwrap_err(), ErrorCode::CommonInvalidStructure);
+        assert_eq!(ReplyHandler::check(r#"{"result":{}}"#).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
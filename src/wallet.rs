@@ -8,6 +8,7 @@ use utils::callbacks::ClosureHandler;
 use utils::results::ResultHandler;
 
 use native::{wallet, non_secrets};
+use storage::{WalletStorage, callbacks};
 use native::{ResponseEmptyCB,
           ResponseStringCB,
           ResponseI32CB};
@@ -242,6 +243,87 @@ impl Wallet {
                                   cb)
     }
 
+    /// Registers a custom wallet storage implemented in Rust.
+    ///
+    /// The crate generates the callbacks libindy expects and manages the handles
+    /// and memory passed to libindy. Use `"storage_type": xtype` in the wallet config
+    /// to create or open a wallet backed by `T`.
+    ///
+    /// # Arguments
+    /// * `xtype` - Wallet storage type name.
+    pub fn register_storage_impl<T: WalletStorage + 'static>(xtype: &str) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Wallet::_register_storage_impl::<T>(command_handle, xtype, cb);
+
+        ResultHandler::empty(err, receiver)
+    }
+
+    /// Registers a custom wallet storage implemented in Rust.
+    ///
+    /// The crate generates the callbacks libindy expects and manages the handles
+    /// and memory passed to libindy. Use `"storage_type": xtype` in the wallet config
+    /// to create or open a wallet backed by `T`.
+    ///
+    /// # Arguments
+    /// * `xtype` - Wallet storage type name.
+    /// * `timeout` - the maximum time this function waits for a response
+    pub fn register_storage_impl_timeout<T: WalletStorage + 'static>(xtype: &str, timeout: Duration) -> Result<(), ErrorCode> {
+        let (receiver, command_handle, cb) = ClosureHandler::cb_ec();
+
+        let err = Wallet::_register_storage_impl::<T>(command_handle, xtype, cb);
+
+        ResultHandler::empty_timeout(err, receiver, timeout)
+    }
+
+    /// Registers a custom wallet storage implemented in Rust.
+    ///
+    /// The crate generates the callbacks libindy expects and manages the handles
+    /// and memory passed to libindy. Use `"storage_type": xtype` in the wallet config
+    /// to create or open a wallet backed by `T`.
+    ///
+    /// # Arguments
+    /// * `xtype` - Wallet storage type name.
+    /// * `closure` - the closure that is called when finished
+    ///
+    /// # Returns
+    /// * `errorcode` - errorcode from calling ffi function. The closure receives the return result
+    pub fn register_storage_impl_async<T: WalletStorage + 'static, F: 'static>(xtype: &str, closure: F) -> ErrorCode where F: FnMut(ErrorCode) + Send {
+        let (command_handle, cb) = ClosureHandler::convert_cb_ec(Box::new(closure));
+
+        Wallet::_register_storage_impl::<T>(command_handle, xtype, cb)
+    }
+
+    fn _register_storage_impl<T: WalletStorage + 'static>(command_handle: IndyHandle, xtype: &str, cb: Option<ResponseEmptyCB>) -> ErrorCode {
+        Wallet::_register_storage(command_handle,
+                                  xtype,
+                                  Some(callbacks::create::<T>),
+                                  Some(callbacks::open::<T>),
+                                  Some(callbacks::close),
+                                  Some(callbacks::delete::<T>),
+                                  Some(callbacks::add_record),
+                                  Some(callbacks::update_record_value),
+                                  Some(callbacks::update_record_tags),
+                                  Some(callbacks::add_record_tags),
+                                  Some(callbacks::delete_record_tags),
+                                  Some(callbacks::delete_record),
+                                  Some(callbacks::get_record),
+                                  Some(callbacks::get_record_id),
+                                  Some(callbacks::get_record_type),
+                                  Some(callbacks::get_record_value),
+                                  Some(callbacks::get_record_tags),
+                                  Some(callbacks::free_record),
+                                  Some(callbacks::get_storage_metadata),
+                                  Some(callbacks::set_storage_metadata),
+                                  Some(callbacks::free_storage_metadata),
+                                  Some(callbacks::search_records),
+                                  Some(callbacks::search_all_records),
+                                  Some(callbacks::get_search_total_count),
+                                  Some(callbacks::fetch_search_next_record),
+                                  Some(callbacks::free_search),
+                                  cb)
+    }
+
     fn _register_storage(command_handle: IndyHandle,
                          xtype: &str,
                          create: Option<wallet::WalletCreate>,
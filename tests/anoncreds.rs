@@ -0,0 +1,222 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::anoncreds::{Prover, Verifier};
+use indy::auto_prover::{AutoProver, FirstCredential};
+use indy::issuer_setup::IssuerSetup;
+use indy::revocation::{RevocationRegistryConfig, RevocationRegistryManager};
+use indy::ErrorCode;
+use utils::anoncreds::{gvt_config, now, proof_request, receive_credential};
+use utils::file::TempDir;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+#[cfg(test)]
+mod test_verify_proof_with_ledger {
+    use super::*;
+
+    #[test]
+    fn verify_proof_with_ledger_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+        let tails_path = tails_dir.as_ref().to_str().unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, RevocationRegistryConfig::new(tails_path, 5));
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+        receive_credential(pool_handle, wallet.handle, &did, &published, Some(&manager));
+
+        let proof_req = proof_request(Some(now() + 100));
+        let proof = AutoProver::create_proof(pool_handle, wallet.handle, &proof_req, "master_secret", tails_path, &FirstCredential {}).unwrap();
+
+        let verified = Verifier::verify_proof_with_ledger(pool_handle, &proof_req, &proof).unwrap();
+
+        assert!(verified.valid);
+        let name = &verified.revealed_attrs["attr1_referent"];
+        assert_eq!(name.raw, "Alex");
+        assert_eq!(name.cred_def_id, published.cred_def_id);
+        assert_eq!(name.schema_id, published.schema_id);
+    }
+
+    #[test]
+    fn verify_proof_with_ledger_returns_attribute_groups() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+        receive_credential(pool_handle, wallet.handle, &did, &published, None);
+
+        let proof_req = json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {"group1_referent": {"names": ["name", "age"]}},
+            "requested_predicates": {}
+        }).to_string();
+        let proof = AutoProver::create_proof(pool_handle, wallet.handle, &proof_req, "master_secret", tails_dir.as_ref().to_str().unwrap(), &FirstCredential {}).unwrap();
+
+        let verified = Verifier::verify_proof_with_ledger(pool_handle, &proof_req, &proof).unwrap();
+
+        assert!(verified.valid);
+        let group = &verified.revealed_attr_groups["group1_referent"];
+        assert_eq!(group.values["name"].raw, "Alex");
+        assert_eq!(group.values["age"].raw, "28");
+        assert_eq!(group.cred_def_id, published.cred_def_id);
+        assert_eq!(group.schema_id, published.schema_id);
+    }
+
+    #[test]
+    fn verify_proof_with_ledger_fails_for_invalid_proof() {
+        let err = Verifier::verify_proof_with_ledger(-1, &proof_request(None), r#"{"proof": {}}"#).unwrap_err();
+
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+    }
+}
+
+#[cfg(test)]
+mod test_rotate_credential_def {
+    use super::*;
+
+    use indy::anoncreds::Issuer;
+    use indy::ledger::Ledger;
+    use serde_json::Value;
+    use utils::anoncreds::CRED_VALUES;
+
+    const INVALID_HANDLE: i32 = 583741;
+
+    fn keys(cred_def_json: &str) -> Value {
+        serde_json::from_str::<Value>(cred_def_json).unwrap()["value"].take()
+    }
+
+    fn ledger_cred_def(pool_handle: i32, did: &str, cred_def_id: &str) -> String {
+        let request = Ledger::build_get_cred_def_request(Some(did), cred_def_id).unwrap();
+        let response = Ledger::submit_request(pool_handle, &request).unwrap();
+
+        Ledger::parse_get_cred_def_response(&response).unwrap().1
+    }
+
+    fn store_credential(wallet_handle: i32, did: &str, cred_def_id: &str, cred_def_json: &str) -> Result<String, ErrorCode> {
+        let cred_offer = Issuer::create_credential_offer(wallet_handle, cred_def_id).unwrap();
+        let (cred_req, cred_req_metadata) = Prover::create_credential_req(wallet_handle, did, &cred_offer, cred_def_json, "master_secret").unwrap();
+        let (cred_json, _, _) = Issuer::create_credential(wallet_handle, &cred_offer, &cred_req, CRED_VALUES, None, -1).unwrap();
+
+        Prover::store_credential(wallet_handle, None, &cred_req_metadata, &cred_json, cred_def_json, None)
+    }
+
+    #[test]
+    fn rotate_credential_def_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let cred_def_json = Issuer::rotate_credential_def(pool_handle, wallet.handle, &did, &published.cred_def_id, None).unwrap();
+
+        assert_ne!(keys(&published.cred_def_json), keys(&cred_def_json));
+        assert_eq!(keys(&cred_def_json), keys(&ledger_cred_def(pool_handle, &did, &published.cred_def_id)));
+
+        store_credential(wallet.handle, &did, &published.cred_def_id, &cred_def_json).unwrap();
+        assert!(store_credential(wallet.handle, &did, &published.cred_def_id, &published.cred_def_json).is_err());
+    }
+
+    #[test]
+    fn rotate_credential_def_start_and_apply_work() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let cred_def_json = Issuer::rotate_credential_def_start(wallet.handle, &published.cred_def_id, None).unwrap();
+
+        assert_ne!(keys(&published.cred_def_json), keys(&cred_def_json));
+        store_credential(wallet.handle, &did, &published.cred_def_id, &published.cred_def_json).unwrap();
+
+        Issuer::rotate_credential_def_apply(wallet.handle, &published.cred_def_id).unwrap();
+
+        store_credential(wallet.handle, &did, &published.cred_def_id, &cred_def_json).unwrap();
+    }
+
+    #[test]
+    fn rotate_credential_def_keeps_old_keys_when_write_fails() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let result = Issuer::rotate_credential_def(INVALID_HANDLE, wallet.handle, &did, &published.cred_def_id, None);
+
+        assert_eq!(ErrorCode::PoolLedgerInvalidPoolHandle, result.unwrap_err());
+        assert_eq!(keys(&published.cred_def_json), keys(&ledger_cred_def(pool_handle, &did, &published.cred_def_id)));
+        store_credential(wallet.handle, &did, &published.cred_def_id, &published.cred_def_json).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test_to_unqualified {
+    use indy::anoncreds::AnonCreds;
+
+    const DID: &str = "NcYxiDXkpYi6ov5FcYDi1e";
+    const SCHEMA_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+
+    #[test]
+    fn to_unqualified_works_for_did() {
+        let qualified = format!("did:sov:{}", DID);
+
+        assert_eq!(DID, AnonCreds::to_unqualified(&qualified).unwrap());
+        assert_eq!(DID, AnonCreds::to_unqualified(DID).unwrap());
+    }
+
+    #[test]
+    fn to_unqualified_works_for_schema_id() {
+        let qualified = format!("schema:sov:did:sov:{}:2:gvt:1.0", DID);
+
+        assert_eq!(SCHEMA_ID, AnonCreds::to_unqualified(&qualified).unwrap());
+        assert_eq!(SCHEMA_ID, AnonCreds::to_unqualified(SCHEMA_ID).unwrap());
+    }
+}
@@ -0,0 +1,102 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::anoncreds::Prover;
+use indy::auto_prover::{AutoProver, CredentialCandidate, CredentialSelector, FirstCredential};
+use indy::issuer_setup::IssuerSetup;
+use indy::revocation::{RevocationRegistryConfig, RevocationRegistryManager};
+use indy::ErrorCode;
+use utils::anoncreds::{gvt_config, now, proof_request, receive_credential};
+use utils::file::TempDir;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+#[cfg(test)]
+mod test_create_proof {
+    use super::*;
+
+    struct NoCredential {}
+
+    impl CredentialSelector for NoCredential {
+        fn select<'a>(&self, _referent: &str, _candidates: &'a [CredentialCandidate]) -> Option<&'a CredentialCandidate> {
+            None
+        }
+    }
+
+    #[test]
+    fn create_proof_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+        receive_credential(pool_handle, wallet.handle, &did, &published, None);
+
+        let proof_req = proof_request(None);
+        let proof = AutoProver::create_proof(pool_handle, wallet.handle, &proof_req, "master_secret", tails_dir.as_ref().to_str().unwrap(), &FirstCredential {}).unwrap();
+
+        let proof: serde_json::Value = serde_json::from_str(&proof).unwrap();
+        assert_eq!(proof["requested_proof"]["revealed_attrs"]["attr1_referent"]["raw"], "Alex");
+    }
+
+    #[test]
+    fn create_proof_works_for_revocable_credential() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+        let tails_path = tails_dir.as_ref().to_str().unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, RevocationRegistryConfig::new(tails_path, 5));
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+        receive_credential(pool_handle, wallet.handle, &did, &published, Some(&manager));
+
+        let proof_req = proof_request(Some(now() + 100));
+        let proof = AutoProver::create_proof(pool_handle, wallet.handle, &proof_req, "master_secret", tails_path, &FirstCredential {}).unwrap();
+
+        let proof: serde_json::Value = serde_json::from_str(&proof).unwrap();
+        assert!(proof["identifiers"][0]["timestamp"].is_number());
+    }
+
+    #[test]
+    fn create_proof_fails_when_nothing_selected() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+        receive_credential(pool_handle, wallet.handle, &did, &published, None);
+
+        let err = AutoProver::create_proof(pool_handle, wallet.handle, &proof_request(None), "master_secret", tails_dir.as_ref().to_str().unwrap(), &NoCredential {}).unwrap_err();
+
+        assert_eq!(err, ErrorCode::WalletItemNotFound);
+    }
+}
@@ -0,0 +1,142 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::anoncreds::Issuer;
+use indy::cache::Cache;
+use indy::ledger::Ledger;
+use indy::ErrorCode;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+const VALID_TIMEOUT: Duration = Duration::from_secs(5);
+const INVALID_TIMEOUT: Duration = Duration::from_micros(1);
+const INVALID_HANDLE: i32 = 583741;
+
+const PURGE_ALL: &str = r#"{"maxAge":-1}"#;
+
+#[cfg(test)]
+mod test_get_schema {
+    use super::*;
+
+    fn publish_schema(pool_handle: i32, wallet_handle: i32, did: &str) -> String {
+        let (schema_id, schema_json) = Issuer::create_schema(did, "gvt", "1.0", r#"["name", "age"]"#).unwrap();
+
+        let request = Ledger::build_schema_request(did, &schema_json).unwrap();
+        Ledger::sign_and_submit_request(pool_handle, wallet_handle, did, &request).unwrap();
+
+        schema_id
+    }
+
+    #[test]
+    fn get_schema_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let schema_id = publish_schema(pool_handle, wallet.handle, &did);
+
+        let schema_json = Cache::get_schema(pool_handle, wallet.handle, &did, &schema_id, "{}").unwrap();
+        let schema: serde_json::Value = serde_json::from_str(&schema_json).unwrap();
+
+        assert_eq!(schema_id, schema["id"].as_str().unwrap());
+    }
+
+    #[test]
+    fn get_schema_from_cache_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let schema_id = publish_schema(pool_handle, wallet.handle, &did);
+
+        let schema_json = Cache::get_schema(pool_handle, wallet.handle, &did, &schema_id, "{}").unwrap();
+
+        let options = json!({"noUpdate": true}).to_string();
+        let cached_schema_json = Cache::get_schema(INVALID_HANDLE, wallet.handle, &did, &schema_id, &options).unwrap();
+
+        assert_eq!(schema_json, cached_schema_json);
+    }
+}
+
+#[cfg(test)]
+mod test_purge_cache {
+    use super::*;
+
+    #[test]
+    fn purge_schema_cache_works() {
+        let wallet = Wallet::new();
+
+        Cache::purge_schema_cache(wallet.handle, PURGE_ALL).unwrap();
+    }
+
+    #[test]
+    fn purge_schema_cache_timeout_works() {
+        let wallet = Wallet::new();
+
+        Cache::purge_schema_cache_timeout(wallet.handle, PURGE_ALL, VALID_TIMEOUT).unwrap();
+    }
+
+    #[test]
+    fn purge_schema_cache_invalid_timeout_error() {
+        let wallet = Wallet::new();
+
+        let ec = Cache::purge_schema_cache_timeout(wallet.handle, PURGE_ALL, INVALID_TIMEOUT).unwrap_err();
+
+        assert_eq!(ec, ErrorCode::CommonIOError);
+    }
+
+    #[test]
+    fn purge_schema_cache_invalid_wallet_handle() {
+        let ec = Cache::purge_schema_cache(INVALID_HANDLE, PURGE_ALL).unwrap_err();
+
+        assert_eq!(ec, ErrorCode::WalletInvalidHandle);
+    }
+
+    #[test]
+    fn purge_cred_def_cache_works() {
+        let wallet = Wallet::new();
+
+        Cache::purge_cred_def_cache(wallet.handle, PURGE_ALL).unwrap();
+    }
+
+    #[test]
+    fn purge_cred_def_cache_async_works() {
+        let wallet = Wallet::new();
+        let (sender, receiver) = channel();
+
+        let cb = move |ec| {
+            sender.send(ec).unwrap();
+        };
+
+        Cache::purge_cred_def_cache_async(wallet.handle, PURGE_ALL, cb);
+        let ec = receiver.recv_timeout(VALID_TIMEOUT).unwrap();
+
+        assert_eq!(ec, ErrorCode::Success);
+    }
+
+    #[test]
+    fn purge_cred_def_cache_invalid_wallet_handle() {
+        let ec = Cache::purge_cred_def_cache(INVALID_HANDLE, PURGE_ALL).unwrap_err();
+
+        assert_eq!(ec, ErrorCode::WalletInvalidHandle);
+    }
+}
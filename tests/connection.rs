@@ -0,0 +1,148 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::connection::{Connection, ConnectionState};
+use indy::crypto::Crypto;
+use indy::did::Did;
+use indy::pairwise::Pairwise;
+use indy::ErrorCode;
+use utils::wallet::Wallet;
+
+#[cfg(test)]
+mod test_connection {
+    use super::*;
+
+    #[test]
+    fn connection_works() {
+        let inviter = Wallet::new();
+        let invitee = Wallet::new();
+
+        let invitation = Connection::create_invitation(inviter.handle, Some("Faber"), Some("https://faber.example.com")).unwrap();
+
+        let request = Connection::request(invitee.handle, &invitation, Some("Alice"), None).unwrap();
+        assert_eq!(Connection::get(invitee.handle, &invitation.did).unwrap().metadata.state, ConnectionState::Requested);
+
+        let (inviter_connection, response) = Connection::respond(inviter.handle, &invitation.did, &request).unwrap();
+        assert_eq!(inviter_connection.my_did, invitation.did);
+        assert_eq!(inviter_connection.metadata.label, Some("Alice".to_string()));
+        assert_eq!(inviter_connection.metadata.state, ConnectionState::Responded);
+
+        let invitee_connection = Connection::complete(invitee.handle, &invitation.did, &response).unwrap();
+        assert_eq!(invitee_connection.my_did, inviter_connection.their_did);
+        assert_eq!(invitee_connection.metadata.label, Some("Faber".to_string()));
+        assert_eq!(invitee_connection.metadata.endpoint, Some("https://faber.example.com".to_string()));
+        assert_eq!(invitee_connection.metadata.state, ConnectionState::Complete);
+
+        assert_eq!(Connection::get(invitee.handle, &invitation.did).unwrap(), invitee_connection);
+        assert_eq!(Connection::get(inviter.handle, &inviter_connection.their_did).unwrap(), inviter_connection);
+    }
+
+    #[test]
+    fn respond_fails_for_used_invitation() {
+        let inviter = Wallet::new();
+        let invitee = Wallet::new();
+
+        let invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+        let request = Connection::request(invitee.handle, &invitation, None, None).unwrap();
+        Connection::respond(inviter.handle, &invitation.did, &request).unwrap();
+
+        let err = Connection::respond(inviter.handle, &invitation.did, &request).unwrap_err();
+
+        assert_eq!(err, ErrorCode::WalletItemNotFound);
+    }
+
+    #[test]
+    fn respond_fails_for_request_with_other_verkey() {
+        let inviter = Wallet::new();
+        let invitee = Wallet::new();
+
+        let invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+
+        let (did, _) = Did::new(invitee.handle, "{}").unwrap();
+        let (_, sender_verkey) = Did::new(invitee.handle, "{}").unwrap();
+        let (_, announced_verkey) = Did::new(invitee.handle, "{}").unwrap();
+        let request = json!({"did": did, "verkey": announced_verkey}).to_string();
+        let request = Crypto::auth_crypt(invitee.handle, &sender_verkey, &invitation.verkey, request.as_bytes()).unwrap();
+
+        let err = Connection::respond(inviter.handle, &invitation.did, &request).unwrap_err();
+
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn respond_fails_for_did_not_derived_from_verkey() {
+        let inviter = Wallet::new();
+        let invitee = Wallet::new();
+
+        let invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+
+        let (_, verkey) = Did::new(invitee.handle, "{}").unwrap();
+        let (other_did, _) = Did::new(invitee.handle, "{}").unwrap();
+        let request = json!({"did": other_did, "verkey": verkey}).to_string();
+        let request = Crypto::auth_crypt(invitee.handle, &verkey, &invitation.verkey, request.as_bytes()).unwrap();
+
+        let err = Connection::respond(inviter.handle, &invitation.did, &request).unwrap_err();
+
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+        assert!(!Pairwise::does_exist(inviter.handle, &other_did).unwrap());
+    }
+
+    #[test]
+    fn respond_fails_for_existing_did() {
+        let inviter = Wallet::new();
+        let invitee = Wallet::new();
+
+        let invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+        let request = Connection::request(invitee.handle, &invitation, None, None).unwrap();
+        let (connection, _) = Connection::respond(inviter.handle, &invitation.did, &request).unwrap();
+
+        let invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+        let verkey = Did::get_ver_key_local(invitee.handle, &connection.their_did).unwrap();
+        let request = json!({"did": connection.their_did, "verkey": verkey}).to_string();
+        let request = Crypto::auth_crypt(invitee.handle, &verkey, &invitation.verkey, request.as_bytes()).unwrap();
+
+        let err = Connection::respond(inviter.handle, &invitation.did, &request).unwrap_err();
+
+        assert_eq!(err, ErrorCode::WalletItemAlreadyExists);
+        assert_eq!(Connection::get(inviter.handle, &connection.their_did).unwrap(), connection);
+    }
+
+    #[test]
+    fn request_fails_for_invitation_did_not_derived_from_verkey() {
+        let inviter = Wallet::new();
+        let invitee = Wallet::new();
+
+        let mut invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+        let (other_did, _) = Did::new(inviter.handle, "{}").unwrap();
+        invitation.did = other_did;
+
+        let err = Connection::request(invitee.handle, &invitation, None, None).unwrap_err();
+
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn complete_fails_for_response_of_other_sender() {
+        let inviter = Wallet::new();
+        let invitee = Wallet::new();
+
+        let invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+        let request = Connection::request(invitee.handle, &invitation, None, None).unwrap();
+        let (inviter_connection, _) = Connection::respond(inviter.handle, &invitation.did, &request).unwrap();
+
+        let my_verkey = Did::get_ver_key_local(invitee.handle, &inviter_connection.their_did).unwrap();
+        let (_, other_verkey) = Did::new(inviter.handle, "{}").unwrap();
+        let response = json!({"did": invitation.did}).to_string();
+        let response = Crypto::auth_crypt(inviter.handle, &other_verkey, &my_verkey, response.as_bytes()).unwrap();
+
+        let err = Connection::complete(invitee.handle, &invitation.did, &response).unwrap_err();
+
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+        assert_eq!(Connection::get(invitee.handle, &invitation.did).unwrap().metadata.state, ConnectionState::Requested);
+    }
+}
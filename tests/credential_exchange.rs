@@ -0,0 +1,85 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::anoncreds::{Issuer, Prover};
+use indy::credential_exchange::CredentialExchange;
+use indy::issuer_setup::IssuerSetup;
+use indy::ErrorCode;
+use utils::anoncreds::gvt_config;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+const CRED_VALUES: &str = r#"{"name": {"raw": "Alex", "encoded": "1139481716457488690172217916278103335"}, "age": {"raw": "28", "encoded": "28"}}"#;
+
+#[cfg(test)]
+mod test_credential_exchange {
+    use super::*;
+
+    #[test]
+    fn request_and_store_credential_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let cred_offer = Issuer::create_credential_offer(wallet.handle, &published.cred_def_id).unwrap();
+        let cred_req = CredentialExchange::request_credential(pool_handle, wallet.handle, "exchange", &did, &cred_offer, "master_secret").unwrap();
+
+        let (cred_json, _, _) = Issuer::create_credential(wallet.handle, &cred_offer, &cred_req, CRED_VALUES, None, -1).unwrap();
+
+        let cred_id = CredentialExchange::store_credential(pool_handle, wallet.handle, "exchange", &cred_json, Some("credential")).unwrap();
+        assert_eq!(cred_id, "credential");
+
+        let credential: serde_json::Value = serde_json::from_str(&Prover::get_credential(wallet.handle, &cred_id).unwrap()).unwrap();
+        assert_eq!(credential["attrs"]["name"], "Alex");
+
+        let err = CredentialExchange::store_credential(pool_handle, wallet.handle, "exchange", &cred_json, None).unwrap_err();
+        assert_eq!(err, ErrorCode::WalletItemNotFound);
+    }
+
+    #[test]
+    fn request_credential_fails_for_duplicate_exchange() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let cred_offer = Issuer::create_credential_offer(wallet.handle, &published.cred_def_id).unwrap();
+        CredentialExchange::request_credential(pool_handle, wallet.handle, "exchange", &did, &cred_offer, "master_secret").unwrap();
+
+        let err = CredentialExchange::request_credential(pool_handle, wallet.handle, "exchange", &did, &cred_offer, "master_secret").unwrap_err();
+        assert_eq!(err, ErrorCode::WalletItemAlreadyExists);
+
+        CredentialExchange::cancel(wallet.handle, "exchange").unwrap();
+        CredentialExchange::request_credential(pool_handle, wallet.handle, "exchange", &did, &cred_offer, "master_secret").unwrap();
+    }
+
+    #[test]
+    fn request_credential_fails_for_invalid_offer() {
+        let wallet = Wallet::new();
+
+        let err = CredentialExchange::request_credential(-1, wallet.handle, "exchange", "V4SGRU86Z58d6TV7PBUe6f", "{}", "master_secret").unwrap_err();
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+    }
+}
@@ -533,6 +533,122 @@ mod replace_keys_apply {
     }
 }
 
+#[cfg(test)]
+mod rotate_key {
+    use super::*;
+
+    use indy::ledger::Ledger;
+    use indy::onboarding::{NymRole, Onboarding};
+
+    #[test]
+    fn rotate_key_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustee_did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, None, NymRole::User, None).unwrap();
+
+        let new_verkey = Did::rotate_key(pool_handle, wallet.handle, &did, &json!({"seed": SEED_1}).to_string()).unwrap();
+
+        assert_eq!(VERKEY_1, new_verkey);
+        assert_eq!(VERKEY_1, Did::get_ver_key_local(wallet.handle, &did).unwrap());
+        assert_eq!(VERKEY_1, Did::get_ver_key(pool_handle, wallet.handle, &did).unwrap());
+    }
+
+    #[test]
+    fn rotate_key_keeps_current_key_when_write_fails() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 0,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+
+        let result = Did::rotate_key(pool_handle, wallet.handle, &did, &json!({"seed": SEED_1}).to_string());
+
+        assert_eq!(ErrorCode::LedgerInvalidTransaction, result.unwrap_err());
+        assert_eq!(verkey, Did::get_ver_key_local(wallet.handle, &did).unwrap());
+    }
+
+    #[test]
+    fn rotate_key_resumes_written_rotation() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustee_did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, None, NymRole::User, None).unwrap();
+
+        // Rotation interrupted after the NYM was written.
+        let new_verkey = Did::replace_keys_start(wallet.handle, &did, &json!({"seed": SEED_1}).to_string()).unwrap();
+        indy::wallet::Wallet::add_record(wallet.handle, Did::PENDING_VERKEY_RECORD, &did, &json!(new_verkey).to_string(), None).unwrap();
+        let request = Ledger::build_nym_request(&did, &did, Some(&new_verkey), None, None).unwrap();
+        Ledger::sign_and_submit_request(pool_handle, wallet.handle, &did, &request).unwrap();
+        assert_eq!(verkey, Did::get_ver_key_local(wallet.handle, &did).unwrap());
+
+        let resumed = Did::rotate_key(pool_handle, wallet.handle, &did, "{}").unwrap();
+
+        assert_eq!(VERKEY_1, resumed);
+        assert_eq!(VERKEY_1, Did::get_ver_key_local(wallet.handle, &did).unwrap());
+        assert_eq!(VERKEY_1, Did::get_ver_key(pool_handle, wallet.handle, &did).unwrap());
+        assert_eq!(ErrorCode::WalletItemNotFound, indy::wallet::Wallet::get_record(wallet.handle, Did::PENDING_VERKEY_RECORD, &did, "{}").unwrap_err());
+    }
+
+    #[test]
+    fn rotate_key_does_not_apply_keys_that_are_not_pending() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustee_did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, None, NymRole::User, None).unwrap();
+
+        // Keys started outside of rotate_key, their verkey is on the ledger but not pending.
+        let new_verkey = Did::replace_keys_start(wallet.handle, &did, &json!({"seed": SEED_1}).to_string()).unwrap();
+        let request = Ledger::build_nym_request(&did, &did, Some(&new_verkey), None, None).unwrap();
+        Ledger::sign_and_submit_request(pool_handle, wallet.handle, &did, &request).unwrap();
+
+        let result = Did::rotate_key(pool_handle, wallet.handle, &did, "{}");
+
+        assert_eq!(ErrorCode::WalletItemNotFound, result.unwrap_err());
+        assert_eq!(verkey, Did::get_ver_key_local(wallet.handle, &did).unwrap());
+    }
+
+    #[test]
+    fn rotate_key_invalid_pool_handle() {
+        let wallet = Wallet::new();
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+
+        let result = Did::rotate_key(INVALID_HANDLE, wallet.handle, &did, "{}");
+
+        assert_eq!(ErrorCode::PoolLedgerInvalidPoolHandle, result.unwrap_err());
+        assert_eq!(verkey, Did::get_ver_key_local(wallet.handle, &did).unwrap());
+    }
+}
+
 #[cfg(test)]
 mod test_store_their_did {
     use super::*;
@@ -1997,3 +2113,81 @@ mod test_get_my_metadata {
         }
     }
 }
+
+#[cfg(test)]
+mod test_qualify_did {
+    use super::*;
+
+    #[test]
+    pub fn qualify_did_success() {
+        let wallet = Wallet::new();
+
+        let (did, _verkey) = Did::new(wallet.handle, "{}").unwrap();
+
+        let qualified_did = Did::qualify_did(wallet.handle, &did, "sov").unwrap();
+
+        assert_eq!(format!("did:sov:{}", did), qualified_did);
+    }
+
+    #[test]
+    pub fn qualify_did_moves_keys() {
+        let wallet = Wallet::new();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+
+        let qualified_did = Did::qualify_did(wallet.handle, &did, "sov").unwrap();
+
+        assert_eq!(verkey, Did::get_ver_key_local(wallet.handle, &qualified_did).unwrap());
+    }
+
+    #[test]
+    pub fn qualify_did_unknown_did_error() {
+        let wallet = Wallet::new();
+
+        let ec = Did::qualify_did(wallet.handle, DID_1, "sov").unwrap_err();
+
+        assert_eq!(ec, ErrorCode::WalletItemNotFound);
+    }
+
+    #[test]
+    pub fn qualify_did_async_success() {
+        let wallet = Wallet::new();
+        let (sender, receiver) = channel();
+        let (did, _verkey) = Did::new(wallet.handle, "{}").unwrap();
+
+        let cb = move |ec, qualified_did| {
+            sender.send((ec, qualified_did)).unwrap();
+        };
+
+        Did::qualify_did_async(wallet.handle, &did, "sov", cb);
+        let (error_code, qualified_did) = receiver.recv_timeout(VALID_TIMEOUT).unwrap();
+
+        assert_eq!(error_code, ErrorCode::Success);
+        assert_eq!(format!("did:sov:{}", did), qualified_did);
+    }
+
+    #[test]
+    pub fn qualify_did_invalid_timeout_error() {
+        let wallet = Wallet::new();
+        let (did, _verkey) = Did::new(wallet.handle, "{}").unwrap();
+
+        let ec = Did::qualify_did_timeout(wallet.handle, &did, "sov", INVALID_TIMEOUT).unwrap_err();
+
+        assert_eq!(ec, ErrorCode::CommonIOError);
+    }
+
+    #[test]
+    pub fn create_did_with_method_name() {
+        let wallet = Wallet::new();
+
+        let config = json!({
+            "seed": SEED_1,
+            "method_name": "sov"
+        }).to_string();
+
+        let (did, verkey) = Did::new(wallet.handle, &config).unwrap();
+
+        assert_eq!(format!("did:sov:{}", DID_1), did);
+        assert_eq!(VERKEY_1, verkey);
+    }
+}
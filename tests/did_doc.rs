@@ -0,0 +1,73 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::did::Did;
+use indy::did_doc::DidResolver;
+use indy::ledger::Ledger;
+use indy::onboarding::{NymRole, Onboarding};
+use indy::ErrorCode;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+#[cfg(test)]
+mod test_resolve {
+    use super::*;
+
+    #[test]
+    fn resolve_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustee_did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, None, NymRole::User, None).unwrap();
+
+        let endpoint = json!({"endpoint": {"endpoint": "https://agent.example.com", "routingKeys": [verkey]}}).to_string();
+        let request = Ledger::build_attrib_request(&did, &did, None, Some(&endpoint), None).unwrap();
+        Ledger::sign_and_submit_request(pool_handle, wallet.handle, &did, &request).unwrap();
+
+        let resolution = DidResolver::resolve(pool_handle, &format!("did:sov:{}", did)).unwrap().unwrap();
+
+        let document = resolution.did_document;
+        assert_eq!(document.id, format!("did:sov:{}", did));
+        assert_eq!(document.verification_method[0].public_key_base58, verkey);
+        assert_eq!(document.authentication, vec![format!("did:sov:{}#key-1", did)]);
+        assert_eq!(document.service.len(), 2);
+        assert_eq!(document.service[1].routing_keys, Some(vec![verkey]));
+        assert!(!resolution.did_document_metadata.deactivated);
+    }
+
+    #[test]
+    fn resolve_returns_none_for_unknown_did() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 0,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+
+        let (did, _) = Did::new(wallet.handle, "{}").unwrap();
+
+        assert_eq!(DidResolver::resolve(pool_handle, &did).unwrap(), None);
+    }
+
+    #[test]
+    fn resolve_fails_for_other_method() {
+        let result = DidResolver::resolve(-1, "did:peer:VsKV7grR1BUE29mG2Fm2kX");
+
+        assert_eq!(result.unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
@@ -0,0 +1,100 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::anoncreds::Issuer;
+use indy::did::Did;
+use indy::endorser::Endorser;
+use indy::ledger::Ledger;
+use indy::onboarding::{NymRole, Onboarding};
+use indy::ErrorCode;
+use utils::constants::DID_1;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+#[cfg(test)]
+mod test_append_request_endorser {
+    use super::*;
+
+    #[test]
+    fn append_request_endorser_works() {
+        let wallet = Wallet::new();
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        let request = Ledger::build_nym_request(&did, DID_1, Some(&verkey), None, None).unwrap();
+
+        let request = Ledger::append_request_endorser(&request, DID_1).unwrap();
+
+        let request: serde_json::Value = serde_json::from_str(&request).unwrap();
+        assert_eq!(request["endorser"], DID_1);
+    }
+
+    #[test]
+    fn append_request_endorser_fails_for_invalid_request() {
+        let result = Ledger::append_request_endorser("not json", DID_1);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
+
+#[cfg(test)]
+mod test_endorse {
+    use super::*;
+
+    #[test]
+    fn endorse_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustee_did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let author_wallet = Wallet::new();
+        let (author_did, author_verkey) = Did::new(author_wallet.handle, "{}").unwrap();
+        Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &author_did, &author_verkey, None, NymRole::User, None).unwrap();
+
+        let (endorser_did, endorser_verkey) = Did::new(wallet.handle, "{}").unwrap();
+        Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &endorser_did, &endorser_verkey, None, NymRole::Endorser, None).unwrap();
+
+        let (_, schema_json) = Issuer::create_schema(&author_did, "gvt", "1.0", r#"["name", "age"]"#).unwrap();
+        let request = Ledger::build_schema_request(&author_did, &schema_json).unwrap();
+
+        let request = Endorser::author_request(author_wallet.handle, &author_did, &endorser_did, &request).unwrap();
+        let response = Endorser::endorse(pool_handle, wallet.handle, &endorser_did, &request).unwrap();
+
+        let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["op"], "REPLY");
+    }
+
+    #[test]
+    fn author_request_fails_for_other_author() {
+        let wallet = Wallet::new();
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        let (other_did, _) = Did::new(wallet.handle, "{}").unwrap();
+        let request = Ledger::build_nym_request(&did, DID_1, Some(&verkey), None, None).unwrap();
+
+        let result = Endorser::author_request(wallet.handle, &other_did, DID_1, &request);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn endorse_fails_for_other_endorser() {
+        let wallet = Wallet::new();
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        let (endorser_did, _) = Did::new(wallet.handle, "{}").unwrap();
+        let request = Ledger::build_nym_request(&did, DID_1, Some(&verkey), None, None).unwrap();
+        let request = Endorser::author_request(wallet.handle, &did, DID_1, &request).unwrap();
+
+        let result = Endorser::endorse(-1, wallet.handle, &endorser_did, &request);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
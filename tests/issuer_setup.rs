@@ -0,0 +1,55 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::did::Did;
+use indy::issuer_setup::{IssuerSetup, ReadRetries};
+use indy::ErrorCode;
+use std::time::Duration;
+use utils::anoncreds::gvt_config;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+const INVALID_HANDLE: i32 = 583741;
+
+#[cfg(test)]
+mod test_publish {
+    use super::*;
+
+    #[test]
+    fn publish_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": false}"#)).unwrap();
+
+        let schema: serde_json::Value = serde_json::from_str(&published.schema_json).unwrap();
+        assert_eq!(published.schema_id, schema["id"].as_str().unwrap());
+        assert!(schema["seqNo"].is_number());
+
+        let cred_def: serde_json::Value = serde_json::from_str(&published.cred_def_json).unwrap();
+        assert_eq!(published.cred_def_id, cred_def["id"].as_str().unwrap());
+        assert_eq!(schema["seqNo"].to_string(), cred_def["schemaId"].as_str().unwrap());
+    }
+
+    #[test]
+    fn publish_fails_for_invalid_pool_handle() {
+        let wallet = Wallet::new();
+        let (did, _) = Did::new(wallet.handle, "{}").unwrap();
+
+        let result = IssuerSetup::publish_with_retries(INVALID_HANDLE, wallet.handle, &did, &gvt_config("{}"), ReadRetries { attempts: 1, delay: Duration::from_millis(0) });
+
+        assert_eq!(result.unwrap_err(), ErrorCode::PoolLedgerInvalidPoolHandle);
+    }
+}
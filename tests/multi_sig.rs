@@ -0,0 +1,63 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::did::Did;
+use indy::ledger::Ledger;
+use indy::multi_sig::MultiSigRequest;
+use indy::ErrorCode;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+#[cfg(test)]
+mod test_multi_sig_request {
+    use super::*;
+
+    #[test]
+    fn multi_sig_request_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 2,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustees = setup.trustees.as_ref().unwrap().dids();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        let request = Ledger::build_nym_request(trustees[0], &did, Some(&verkey), None, None).unwrap();
+
+        let mut coordinator = MultiSigRequest::new(&request, &trustees, 2).unwrap();
+        let shared = coordinator.to_json().unwrap();
+
+        for trustee in &trustees {
+            let mut copy = MultiSigRequest::from_json(&shared).unwrap();
+            copy.sign(wallet.handle, trustee).unwrap();
+
+            coordinator.merge(&MultiSigRequest::from_json(&copy.to_json().unwrap()).unwrap()).unwrap();
+        }
+
+        assert!(coordinator.has_quorum());
+        assert!(coordinator.missing_signers().is_empty());
+
+        let response = coordinator.submit(pool_handle).unwrap();
+        let response: serde_json::Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["op"], "REPLY");
+    }
+
+    #[test]
+    fn sign_fails_for_other_did() {
+        let wallet = Wallet::new();
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        let request = json!({"reqId": 1, "identifier": did, "operation": {"type": "1", "dest": did, "verkey": verkey}}).to_string();
+
+        let mut request = MultiSigRequest::new(&request, &["VsKV7grR1BUE29mG2Fm2kX"], 1).unwrap();
+
+        assert_eq!(request.sign(wallet.handle, &did).unwrap_err(), ErrorCode::CommonInvalidStructure);
+    }
+}
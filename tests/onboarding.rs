@@ -0,0 +1,84 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::did::Did;
+use indy::onboarding::{EndpointAttrib, LedgerUpdate, NymRole, Onboarding};
+use indy::ErrorCode;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+const INVALID_HANDLE: i32 = 583741;
+
+#[cfg(test)]
+mod test_onboard {
+    use super::*;
+
+    #[test]
+    fn onboard_works() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustee_did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        let endpoint = EndpointAttrib { wallet_handle: wallet.handle, address: "127.0.0.1:9700".to_string(), transport_key: Some(verkey.clone()) };
+
+        let onboarded = Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, Some("endorser"), NymRole::Endorser, Some(&endpoint)).unwrap();
+
+        assert_eq!(onboarded.did, did);
+        assert_eq!(onboarded.nym, LedgerUpdate::Written);
+        assert_eq!(onboarded.endpoint, Some(LedgerUpdate::Written));
+
+        let (address, transport_key) = Did::get_endpoint(wallet.handle, pool_handle, &did).unwrap();
+        assert_eq!(address, "127.0.0.1:9700");
+        assert_eq!(transport_key, Some(verkey));
+    }
+
+    #[test]
+    fn onboard_is_idempotent() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let trustee_did = setup.trustees.as_ref().unwrap()[0].did.clone();
+
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+        let endpoint = EndpointAttrib { wallet_handle: wallet.handle, address: "127.0.0.1:9700".to_string(), transport_key: None };
+
+        Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, None, NymRole::Endorser, Some(&endpoint)).unwrap();
+        let onboarded = Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, None, NymRole::Endorser, Some(&endpoint)).unwrap();
+
+        assert_eq!(onboarded.nym, LedgerUpdate::Unchanged);
+        assert_eq!(onboarded.endpoint, Some(LedgerUpdate::Unchanged));
+
+        let onboarded = Onboarding::onboard(pool_handle, wallet.handle, &trustee_did, &did, &verkey, None, NymRole::User, None).unwrap();
+
+        assert_eq!(onboarded.nym, LedgerUpdate::Written);
+        assert_eq!(onboarded.endpoint, None);
+    }
+
+    #[test]
+    fn onboard_fails_for_invalid_pool_handle() {
+        let wallet = Wallet::new();
+        let (submitter_did, _) = Did::new(wallet.handle, "{}").unwrap();
+        let (did, verkey) = Did::new(wallet.handle, "{}").unwrap();
+
+        let result = Onboarding::onboard(INVALID_HANDLE, wallet.handle, &submitter_did, &did, &verkey, None, NymRole::Endorser, None);
+
+        assert_eq!(result.unwrap_err(), ErrorCode::PoolLedgerInvalidPoolHandle);
+    }
+}
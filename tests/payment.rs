@@ -6,8 +6,20 @@ extern crate byteorder;
 #[macro_use]
 mod utils;
 
+use indy::ErrorCode;
+use indy::ledger::Ledger;
+use indy::payment_method::null::NullPayment;
+use indy::payments::Payment;
 use indy::wallet::Wallet;
-use utils::constants::DEFAULT_CREDENTIALS;
+use utils::constants::{DEFAULT_CREDENTIALS, DID_1};
+
+use std::sync::{Once, ONCE_INIT};
+
+fn register_null_payment() {
+    static REGISTER: Once = ONCE_INIT;
+
+    REGISTER.call_once(|| NullPayment::register().unwrap());
+}
 
 mod low_tests {
     use super::*;
@@ -21,3 +33,122 @@ mod low_tests {
         wallet_cleanup!(handle, wallet_name);
     }
 }
+
+#[cfg(test)]
+mod test_null_payment {
+    use super::*;
+
+    use serde_json::Value;
+
+    fn create_address(wallet_handle: i32) -> String {
+        register_null_payment();
+        Payment::create_payment_address(wallet_handle, NullPayment::METHOD, "{}").unwrap()
+    }
+
+    fn mint(wallet_handle: i32, address: &str, amount: u64) -> Vec<String> {
+        let outputs = json!([{"recipient": address, "amount": amount}]).to_string();
+        let (mint_req, payment_method) = Payment::build_mint_req(wallet_handle, None, &outputs, None).unwrap();
+        assert_eq!(payment_method, NullPayment::METHOD);
+
+        let receipts: Value = serde_json::from_str(&Payment::parse_payment_response(NullPayment::METHOD, &mint_req).unwrap()).unwrap();
+        receipts.as_array().unwrap().iter().map(|receipt| receipt["receipt"].as_str().unwrap().to_string()).collect()
+    }
+
+    fn balance(wallet_handle: i32, address: &str) -> u64 {
+        let (get_sources_req, _) = Payment::build_get_payment_sources_request(wallet_handle, None, address).unwrap();
+        let sources: Value = serde_json::from_str(&Payment::parse_get_payment_sources_response(NullPayment::METHOD, &get_sources_req).unwrap()).unwrap();
+
+        sources.as_array().unwrap().iter().map(|source| source["amount"].as_u64().unwrap()).sum()
+    }
+
+    #[test]
+    fn create_payment_address_works() {
+        let wallet = utils::wallet::Wallet::new();
+
+        let address = create_address(wallet.handle);
+        assert!(address.starts_with("pay:null:"));
+
+        let addresses = Payment::list_payment_addresses(wallet.handle).unwrap();
+        assert!(addresses.contains(&address));
+    }
+
+    #[test]
+    fn mint_works() {
+        let wallet = utils::wallet::Wallet::new();
+        let address = create_address(wallet.handle);
+
+        let receipts = mint(wallet.handle, &address, 100);
+
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(balance(wallet.handle, &address), 100);
+    }
+
+    #[test]
+    fn payment_works() {
+        let wallet = utils::wallet::Wallet::new();
+        let from = create_address(wallet.handle);
+        let to = create_address(wallet.handle);
+        let inputs = json!(mint(wallet.handle, &from, 100)).to_string();
+        let outputs = json!([{"recipient": to, "amount": 60}, {"recipient": from, "amount": 40}]).to_string();
+
+        let (payment_req, _) = Payment::build_payment_req(wallet.handle, None, &inputs, &outputs, None).unwrap();
+        let receipts: Value = serde_json::from_str(&Payment::parse_payment_response(NullPayment::METHOD, &payment_req).unwrap()).unwrap();
+
+        assert_eq!(receipts.as_array().unwrap().len(), 2);
+        assert_eq!(balance(wallet.handle, &from), 40);
+        assert_eq!(balance(wallet.handle, &to), 60);
+    }
+
+    #[test]
+    fn payment_fails_for_insufficient_funds() {
+        let wallet = utils::wallet::Wallet::new();
+        let address = create_address(wallet.handle);
+        let inputs = json!(mint(wallet.handle, &address, 10)).to_string();
+        let outputs = json!([{"recipient": address, "amount": 20}]).to_string();
+
+        let result = Payment::build_payment_req(wallet.handle, None, &inputs, &outputs, None);
+
+        assert_eq!(ErrorCode::PaymentInsufficientFundsError, result.unwrap_err());
+    }
+
+    #[test]
+    fn verify_payment_works() {
+        let wallet = utils::wallet::Wallet::new();
+        let address = create_address(wallet.handle);
+        let inputs = mint(wallet.handle, &address, 10);
+        let outputs = json!([{"recipient": address, "amount": 10}]).to_string();
+
+        let (payment_req, _) = Payment::build_payment_req(wallet.handle, None, &json!(inputs).to_string(), &outputs, Some("extra")).unwrap();
+        let receipts: Value = serde_json::from_str(&Payment::parse_payment_response(NullPayment::METHOD, &payment_req).unwrap()).unwrap();
+        let receipt = receipts[0]["receipt"].as_str().unwrap();
+
+        let (verify_req, _) = Payment::build_verify_req(wallet.handle, None, receipt).unwrap();
+        let txn: Value = serde_json::from_str(&Payment::parse_verify_response(NullPayment::METHOD, &verify_req).unwrap()).unwrap();
+
+        assert_eq!(txn["sources"], json!(inputs));
+        assert_eq!(txn["extra"], "extra");
+    }
+
+    #[test]
+    fn txn_fees_work() {
+        let wallet = utils::wallet::Wallet::new();
+        let address = create_address(wallet.handle);
+        let fees = json!({"1": 5}).to_string();
+
+        Payment::build_set_txn_fees_req(wallet.handle, None, NullPayment::METHOD, &fees).unwrap();
+
+        let get_fees_req = Payment::build_get_txn_fees_req(wallet.handle, None, NullPayment::METHOD).unwrap();
+        let fees: Value = serde_json::from_str(&Payment::parse_get_txn_fees_response(NullPayment::METHOD, &get_fees_req).unwrap()).unwrap();
+        assert_eq!(fees["1"], 5);
+
+        let nym_req = Ledger::build_nym_request(DID_1, DID_1, None, None, None).unwrap();
+        let inputs = json!(mint(wallet.handle, &address, 10)).to_string();
+        let outputs = json!([{"recipient": address, "amount": 5}]).to_string();
+
+        let (nym_req_with_fees, _) = Payment::add_request_fees(wallet.handle, None, &nym_req, &inputs, &outputs, None).unwrap();
+        let receipts: Value = serde_json::from_str(&Payment::parse_response_with_fees(NullPayment::METHOD, &nym_req_with_fees).unwrap()).unwrap();
+
+        assert_eq!(receipts[0]["amount"], 5);
+        assert_eq!(balance(wallet.handle, &address), 5);
+    }
+}
@@ -0,0 +1,220 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::anoncreds::{Issuer, Prover};
+use indy::issuer_setup::IssuerSetup;
+use indy::revocation::{RevocationRegistryConfig, RevocationRegistryManager, RevocationRegistryState};
+use utils::anoncreds::gvt_config;
+use utils::file::TempDir;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+const INVALID_HANDLE: i32 = 583741;
+
+const CRED_VALUES: &str = r#"{"name": {"raw": "Alex", "encoded": "1139481716457488690172217916278103335"}, "age": {"raw": "28", "encoded": "28"}}"#;
+
+#[cfg(test)]
+mod test_revocation_registry_manager {
+    use super::*;
+
+    pub fn issue(manager: &RevocationRegistryManager, wallet_handle: i32, prover_did: &str, cred_def_id: &str, cred_def_json: &str, cred_id: Option<&str>) -> indy::revocation::IssuedCredential {
+        let cred_offer = Issuer::create_credential_offer(wallet_handle, cred_def_id).unwrap();
+        let (cred_req, _) = Prover::create_credential_req(wallet_handle, prover_did, &cred_offer, cred_def_json, "master_secret").unwrap();
+
+        manager.issue_credential(&cred_offer, &cred_req, CRED_VALUES, cred_id).unwrap()
+    }
+
+    #[test]
+    fn issue_credential_rolls_over_to_next_registry() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let config = RevocationRegistryConfig::new(tails_dir.as_ref().to_str().unwrap(), 1);
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, config);
+        assert_eq!(manager.active_registry().unwrap(), None);
+
+        let first = issue(&manager, wallet.handle, &did, &published.cred_def_id, &published.cred_def_json, Some("first"));
+        let second = issue(&manager, wallet.handle, &did, &published.cred_def_id, &published.cred_def_json, None);
+
+        assert_ne!(first.location.rev_reg_id, second.location.rev_reg_id);
+        assert_eq!(manager.credential_location("first").unwrap(), Some(first.location.clone()));
+        assert_eq!(manager.credential_location(&second.cred_id).unwrap(), Some(second.location.clone()));
+
+        let registries = manager.registries().unwrap();
+        assert_eq!(registries.len(), 2);
+        assert_eq!(manager.registry(&first.location.rev_reg_id).unwrap().unwrap().state, RevocationRegistryState::Full);
+        assert_eq!(manager.active_registry().unwrap().unwrap().rev_reg_id, second.location.rev_reg_id);
+    }
+
+    #[test]
+    fn manager_continues_with_registries_from_wallet() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        let config = RevocationRegistryConfig::new(tails_dir.as_ref().to_str().unwrap(), 5);
+
+        let registry = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, config.clone()).create_registry().unwrap();
+
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, config);
+        assert_eq!(manager.active_registry().unwrap(), Some(registry));
+    }
+
+    #[test]
+    fn issue_credential_returns_unpublished_delta() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let mut config = RevocationRegistryConfig::new(tails_dir.as_ref().to_str().unwrap(), 5);
+        config.issuance_type = Some("ISSUANCE_ON_DEMAND".to_string());
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, config.clone());
+        manager.create_registry().unwrap();
+
+        let offline = RevocationRegistryManager::new(INVALID_HANDLE, wallet.handle, &did, &published.cred_def_id, config);
+        let issued = issue(&offline, wallet.handle, &did, &published.cred_def_id, &published.cred_def_json, Some("offline"));
+
+        assert_eq!(manager.credential_location("offline").unwrap(), Some(issued.location.clone()));
+
+        let delta = issued.unpublished_delta.unwrap();
+        manager.publish_delta(&issued.location.rev_reg_id, &delta).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test_revoke_credentials {
+    use super::*;
+
+    use indy::ErrorCode;
+    use indy::revocation::CredentialLocation;
+
+    #[test]
+    fn revoke_credentials_publishes_one_entry_per_registry() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let config = RevocationRegistryConfig::new(tails_dir.as_ref().to_str().unwrap(), 2);
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, config);
+
+        let credentials: Vec<CredentialLocation> = (0..3)
+            .map(|_| test_revocation_registry_manager::issue(&manager, wallet.handle, &did, &published.cred_def_id, &published.cred_def_json, None).location)
+            .collect();
+        let unknown = CredentialLocation { rev_reg_id: credentials[0].rev_reg_id.clone(), cred_rev_id: "100".to_string() };
+
+        let mut to_revoke = credentials.clone();
+        to_revoke.push(unknown.clone());
+
+        let report = manager.revoke_credentials(&to_revoke).unwrap();
+
+        assert_eq!(report.revoked, credentials);
+        assert_eq!(report.published.len(), 2);
+        assert!(report.published.values().all(|deltas| deltas.len() == 1));
+        assert_eq!(report.failed, vec![(unknown, ErrorCode::AnoncredsInvalidUserRevocIndex)]);
+    }
+
+    #[test]
+    fn revoke_credentials_returns_unpublished_delta() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+
+        let config = RevocationRegistryConfig::new(tails_dir.as_ref().to_str().unwrap(), 2);
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, config.clone());
+        let credential = test_revocation_registry_manager::issue(&manager, wallet.handle, &did, &published.cred_def_id, &published.cred_def_json, None).location;
+
+        let offline = RevocationRegistryManager::new(INVALID_HANDLE, wallet.handle, &did, &published.cred_def_id, config);
+        let report = offline.revoke_credentials(&[credential.clone()]).unwrap();
+
+        assert!(report.revoked.is_empty());
+        assert!(report.published.is_empty());
+        assert_eq!(report.failed, vec![(credential.clone(), ErrorCode::PoolLedgerInvalidPoolHandle)]);
+
+        let deltas = &report.unpublished[&credential.rev_reg_id];
+        assert_eq!(deltas.len(), 1);
+        manager.publish_delta(&credential.rev_reg_id, &deltas[0]).unwrap();
+    }
+
+    #[test]
+    fn revoke_credentials_reports_unknown_registry() {
+        let wallet = Wallet::new();
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let config = RevocationRegistryConfig::new(tails_dir.as_ref().to_str().unwrap(), 2);
+        let manager = RevocationRegistryManager::new(-1, wallet.handle, "V4SGRU86Z58d6TV7PBUe6f", "V4SGRU86Z58d6TV7PBUe6f:3:CL:1:tag", config);
+        let credential = CredentialLocation { rev_reg_id: "unknown".to_string(), cred_rev_id: "1".to_string() };
+
+        let report = manager.revoke_credentials(&[credential.clone()]).unwrap();
+
+        assert!(report.revoked.is_empty());
+        assert_eq!(report.failed, vec![(credential, ErrorCode::WalletItemNotFound)]);
+    }
+
+    #[test]
+    fn revoke_credentials_reports_wallet_error() {
+        let tails_dir = TempDir::new(None).unwrap();
+
+        let config = RevocationRegistryConfig::new(tails_dir.as_ref().to_str().unwrap(), 2);
+        let manager = RevocationRegistryManager::new(-1, INVALID_HANDLE, "V4SGRU86Z58d6TV7PBUe6f", "V4SGRU86Z58d6TV7PBUe6f:3:CL:1:tag", config);
+        let credential = CredentialLocation { rev_reg_id: "unknown".to_string(), cred_rev_id: "1".to_string() };
+
+        let report = manager.revoke_credentials(&[credential.clone()]).unwrap();
+
+        assert!(report.revoked.is_empty());
+        assert_eq!(report.failed, vec![(credential, ErrorCode::WalletInvalidHandle)]);
+    }
+}
@@ -0,0 +1,71 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::anoncreds::Prover;
+use indy::auto_prover::{CredentialInfo, NonRevokedInterval};
+use indy::issuer_setup::IssuerSetup;
+use indy::revocation::{RevocationRegistryConfig, RevocationRegistryManager};
+use indy::revocation_state::RevocationStateCache;
+use indy::ErrorCode;
+use utils::anoncreds::{gvt_config, now, receive_credential};
+use utils::file::TempDir;
+use utils::setup::{Setup, SetupConfig};
+use utils::wallet::Wallet;
+
+#[cfg(test)]
+mod test_revocation_state_cache {
+    use super::*;
+
+    #[test]
+    fn get_returns_cached_state_within_interval() {
+        let wallet = Wallet::new();
+        let setup = Setup::new(&wallet, SetupConfig {
+            connect_to_pool: true,
+            num_trustees: 1,
+            num_users: 0,
+            num_nodes: 4
+        });
+        let pool_handle = setup.pool_handle.unwrap();
+        let did = setup.trustees.as_ref().unwrap()[0].did.clone();
+        let tails_dir = TempDir::new(None).unwrap();
+        let tails_path = tails_dir.as_ref().to_str().unwrap();
+
+        let published = IssuerSetup::publish(pool_handle, wallet.handle, &did, &gvt_config(r#"{"support_revocation": true}"#)).unwrap();
+        let manager = RevocationRegistryManager::new(pool_handle, wallet.handle, &did, &published.cred_def_id, RevocationRegistryConfig::new(tails_path, 5));
+        Prover::create_master_secret(wallet.handle, Some("master_secret")).unwrap();
+        receive_credential(pool_handle, wallet.handle, &did, &published, Some(&manager));
+
+        let credentials: Vec<CredentialInfo> = serde_json::from_str(&Prover::get_credentials(wallet.handle, None).unwrap()).unwrap();
+        let interval = NonRevokedInterval { from: None, to: Some(now() + 100) };
+
+        let (rev_state, timestamp) = RevocationStateCache::get(pool_handle, wallet.handle, &credentials[0], &interval, tails_path).unwrap();
+
+        let interval = NonRevokedInterval { from: Some(timestamp), to: Some(now() + 200) };
+        let cached = RevocationStateCache::get(-1, wallet.handle, &credentials[0], &interval, tails_path).unwrap();
+
+        assert_eq!(cached, (rev_state, timestamp));
+    }
+
+    #[test]
+    fn get_fails_for_not_revocable_credential() {
+        let wallet = Wallet::new();
+        let cred_info = CredentialInfo {
+            referent: "credential".to_string(),
+            attrs: Default::default(),
+            schema_id: "schema".to_string(),
+            cred_def_id: "cred_def".to_string(),
+            rev_reg_id: None,
+            cred_rev_id: None,
+        };
+        let interval = NonRevokedInterval { from: None, to: Some(now()) };
+
+        let err = RevocationStateCache::get(-1, wallet.handle, &cred_info, &interval, "tails").unwrap_err();
+
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+    }
+}
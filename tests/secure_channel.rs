@@ -0,0 +1,107 @@
+#[macro_use] extern crate serde_json;
+#[macro_use] extern crate serde_derive;
+extern crate rmp_serde;
+extern crate byteorder;
+extern crate rust_libindy_wrapper as indy;
+#[macro_use]
+mod utils;
+
+use indy::connection::Connection;
+use indy::crypto::Crypto;
+use indy::did::Did;
+use indy::secure_channel::SecureChannel;
+use indy::ErrorCode;
+use utils::wallet::Wallet;
+
+/// Connects two wallets and returns the DIDs of the inviter and the invitee.
+fn connect(inviter: &Wallet, invitee: &Wallet) -> (String, String) {
+    let invitation = Connection::create_invitation(inviter.handle, None, None).unwrap();
+    let request = Connection::request(invitee.handle, &invitation, None, None).unwrap();
+    let (connection, response) = Connection::respond(inviter.handle, &invitation.did, &request).unwrap();
+    Connection::complete(invitee.handle, &invitation.did, &response).unwrap();
+
+    (connection.my_did, connection.their_did)
+}
+
+#[cfg(test)]
+mod test_secure_channel {
+    use super::*;
+
+    #[test]
+    fn send_and_receive_works() {
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let (alice_did, bob_did) = connect(&alice, &bob);
+
+        let alice_channel = SecureChannel::open(alice.handle, &bob_did).unwrap();
+        let bob_channel = SecureChannel::open(bob.handle, &alice_did).unwrap();
+
+        let first = alice_channel.send("thread", b"hello").unwrap();
+        let second = alice_channel.send("thread", b"bye").unwrap();
+
+        let received = bob_channel.receive(&first).unwrap();
+        assert_eq!(received.thread_id, "thread");
+        assert_eq!(received.seq, 1);
+        assert_eq!(received.body, b"hello".to_vec());
+
+        assert_eq!(bob_channel.receive(&second).unwrap().seq, 2);
+
+        let answer = bob_channel.send("thread", b"hi").unwrap();
+        assert_eq!(alice_channel.receive(&answer).unwrap().body, b"hi".to_vec());
+    }
+
+    #[test]
+    fn receive_rejects_replay() {
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let (alice_did, bob_did) = connect(&alice, &bob);
+
+        let message = SecureChannel::open(alice.handle, &bob_did).unwrap().send("thread", b"hello").unwrap();
+
+        let bob_channel = SecureChannel::open(bob.handle, &alice_did).unwrap();
+        bob_channel.receive(&message).unwrap();
+
+        assert_eq!(bob_channel.receive(&message).unwrap_err(), ErrorCode::CommonInvalidState);
+        assert_eq!(SecureChannel::open(bob.handle, &alice_did).unwrap().receive(&message).unwrap_err(), ErrorCode::CommonInvalidState);
+    }
+
+    #[test]
+    fn receive_rejects_reordering() {
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let (alice_did, bob_did) = connect(&alice, &bob);
+
+        let alice_channel = SecureChannel::open(alice.handle, &bob_did).unwrap();
+        let first = alice_channel.send("thread", b"first").unwrap();
+        let second = alice_channel.send("thread", b"second").unwrap();
+
+        let bob_channel = SecureChannel::open(bob.handle, &alice_did).unwrap();
+        bob_channel.receive(&second).unwrap();
+
+        assert_eq!(bob_channel.receive(&first).unwrap_err(), ErrorCode::CommonInvalidState);
+    }
+
+    #[test]
+    fn receive_rejects_other_sender() {
+        let alice = Wallet::new();
+        let bob = Wallet::new();
+        let (alice_did, bob_did) = connect(&alice, &bob);
+
+        let (_, other_verkey) = Did::new(alice.handle, "{}").unwrap();
+        let bob_verkey = Did::get_ver_key_local(alice.handle, &bob_did).unwrap();
+        let envelope = json!({"from": alice_did, "to": bob_did, "thread_id": "thread", "seq": 1, "timestamp": 0, "body": []}).to_string();
+        let message = Crypto::auth_crypt(alice.handle, &other_verkey, &bob_verkey, envelope.as_bytes()).unwrap();
+
+        let err = SecureChannel::open(bob.handle, &alice_did).unwrap().receive(&message).unwrap_err();
+
+        assert_eq!(err, ErrorCode::CommonInvalidStructure);
+    }
+
+    #[test]
+    fn open_fails_without_pairwise() {
+        let wallet = Wallet::new();
+        let (did, _) = Did::new(wallet.handle, "{}").unwrap();
+
+        assert_eq!(SecureChannel::open(wallet.handle, &did).err(), Some(ErrorCode::WalletItemNotFound));
+    }
+}
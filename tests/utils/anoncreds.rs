@@ -0,0 +1,55 @@
+use indy::anoncreds::Issuer;
+use indy::credential_exchange::CredentialExchange;
+use indy::issuer_setup::{CredentialDefConfig, PublishedCredentialDef};
+use indy::revocation::RevocationRegistryManager;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const CRED_VALUES: &str = r#"{"name": {"raw": "Alex", "encoded": "1139481716457488690172217916278103335"}, "age": {"raw": "28", "encoded": "28"}}"#;
+
+/// The `gvt` schema with a credential definition configured by `config_json`.
+pub fn gvt_config<'a>(config_json: &'a str) -> CredentialDefConfig<'a> {
+    CredentialDefConfig {
+        name: "gvt",
+        version: "1.0",
+        attrs: r#"["name", "age"]"#,
+        tag: "tag",
+        signature_type: None,
+        config_json,
+    }
+}
+
+/// A proof request for the `gvt` name and age >= 18, non-revoked at `non_revoked` if given.
+pub fn proof_request(non_revoked: Option<u64>) -> String {
+    let mut proof_req = json!({
+        "nonce": "123432421212",
+        "name": "proof_req_1",
+        "version": "0.1",
+        "requested_attributes": {"attr1_referent": {"name": "name"}},
+        "requested_predicates": {"predicate1_referent": {"name": "age", "p_type": ">=", "p_value": 18}}
+    });
+
+    if let Some(to) = non_revoked {
+        proof_req["non_revoked"] = json!({"to": to});
+    }
+
+    proof_req.to_string()
+}
+
+/// Issues a credential with `CRED_VALUES` (revocable if `manager` is given) and stores it in the wallet.
+pub fn receive_credential(pool_handle: i32, wallet_handle: i32, did: &str, published: &PublishedCredentialDef, manager: Option<&RevocationRegistryManager>) {
+    let cred_offer = Issuer::create_credential_offer(wallet_handle, &published.cred_def_id).unwrap();
+    let cred_req = CredentialExchange::request_credential(pool_handle, wallet_handle, "exchange", did, &cred_offer, "master_secret").unwrap();
+
+    let cred_json = match manager {
+        Some(manager) => manager.issue_credential(&cred_offer, &cred_req, CRED_VALUES, None).unwrap().cred_json,
+        None => Issuer::create_credential(wallet_handle, &cred_offer, &cred_req, CRED_VALUES, None, -1).unwrap().0
+    };
+
+    CredentialExchange::store_credential(pool_handle, wallet_handle, "exchange", &cred_json, None).unwrap();
+}
+
+/// Current time in seconds since the epoch.
+pub fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
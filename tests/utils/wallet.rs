@@ -1,7 +1,10 @@
 use super::indy;
 use indy::ErrorCode;
+use indy::storage::inmem::InmemStorage;
 use utils::rand::random_string;
 
+use std::sync::{Once, ONCE_INIT};
+
 static USEFUL_CREDENTIALS : &'static str = r#"
    {
        "key": "12345678901234567890123456789012"
@@ -11,6 +14,9 @@ static USEFUL_CREDENTIALS : &'static str = r#"
 /**
 A test wallet that deletees itself when it leaves scope.
 
+The wallet is kept in memory using the `inmem` storage type,
+so closing its only handle also deletes it.
+
 Use by calling `let wallet = Wallet::new();` and pass the `wallet.handle`.
 
 ```
@@ -51,9 +57,18 @@ impl Wallet {
         return wallet;
     }
 
+    /* registers the inmem storage type once per test process */
+    pub fn register_inmem_storage() {
+        static REGISTER: Once = ONCE_INIT;
+
+        REGISTER.call_once(|| InmemStorage::register().unwrap());
+    }
+
     /* private static method to help create config that is passed to wallet functions */
     fn create_wallet_config(wallet_name: &str) -> String {
-        let config = json!({ "id" : wallet_name.to_string() }).to_string();
+        Wallet::register_inmem_storage();
+
+        let config = json!({ "id" : wallet_name.to_string(), "storage_type": InmemStorage::TYPE }).to_string();
         return config.to_string();
     }
 
@@ -75,15 +90,10 @@ impl Wallet {
         indy::wallet::Wallet::close(self.handle)
     }
 
-    fn delete(&self) -> Result<(), ErrorCode> {
-        let config : String = Wallet::create_wallet_config(&self.name);
-        return indy::wallet::Wallet::delete(&config, USEFUL_CREDENTIALS)
-    }
 }
 
 impl Drop for Wallet {
     fn drop(&mut self) {
         self.close().unwrap();
-        self.delete().unwrap();
     }
 }
@@ -154,10 +154,31 @@ mod test_wallet_create {
         Wallet::delete(&config, CREDENTIALS).unwrap();
     }
 
-    // #[test]
-    // fn create_wallet_custom_storage_type() {
-    //     unimplemented!();
-    // }
+    #[test]
+    fn create_wallet_custom_storage_type() {
+        utils::wallet::Wallet::register_inmem_storage();
+        let config = wallet_config(Some("inmem"));
+
+        let result = Wallet::create(&config, CREDENTIALS);
+
+        assert_eq!((), result.unwrap());
+
+        let handle = Wallet::open(&config, CREDENTIALS).unwrap();
+        Wallet::close(handle).unwrap();
+
+        assert_eq!(ErrorCode::WalletNotFoundError, Wallet::open(&config, CREDENTIALS).unwrap_err());
+    }
+
+    #[test]
+    fn delete_wallet_custom_storage_type() {
+        utils::wallet::Wallet::register_inmem_storage();
+        let config = wallet_config(Some("inmem"));
+
+        Wallet::create(&config, CREDENTIALS).unwrap();
+        Wallet::delete(&config, CREDENTIALS).unwrap();
+
+        assert_eq!(ErrorCode::WalletNotFoundError, Wallet::open(&config, CREDENTIALS).unwrap_err());
+    }
 
     #[test]
     fn create_wallet_unknown_storage_type() {